@@ -0,0 +1,160 @@
+use darling::{
+    ast::{Data, Fields, Style},
+    util::Ignored,
+    FromDeriveInput, FromField, FromVariant,
+};
+use proc_macro2::{Ident, Literal, TokenStream};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use syn::{spanned::Spanned, DeriveInput, Path, Type, Visibility};
+
+use crate::{one_response::StatusAttr, GeneratorResult};
+
+#[derive(FromField, Debug)]
+struct ErrorField {
+    ident: Option<Ident>,
+}
+
+#[derive(FromVariant, Debug)]
+#[darling(attributes(oai))]
+struct ErrorVariant {
+    ident: Ident,
+    fields: Fields<ErrorField>,
+
+    status: StatusAttr,
+    response: Type,
+    #[darling(default)]
+    with: Option<Path>,
+}
+
+#[derive(FromDeriveInput, Debug)]
+#[darling(attributes(oai))]
+struct ErrorArgs {
+    ident: Ident,
+    vis: Visibility,
+    generics: syn::Generics,
+    data: Data<ErrorVariant, Ignored>,
+
+    #[darling(default, multiple)]
+    into: Vec<Type>,
+}
+
+pub(crate) fn generate(args: DeriveInput) -> GeneratorResult<TokenStream> {
+    let args: ErrorArgs = ErrorArgs::from_derive_input(&args)?;
+    let error_ident = &args.ident;
+
+    let variants = match &args.data {
+        Data::Enum(variants) if !variants.is_empty() => variants,
+        _ => {
+            return Err(syn::Error::new(
+                error_ident.span(),
+                "ErrorResponse can only be applied to an enum with at least one variant.",
+            )
+            .into())
+        }
+    };
+    if !args.generics.params.is_empty() {
+        return Err(syn::Error::new(
+            error_ident.span(),
+            "ErrorResponse cannot be applied to a generic enum.",
+        )
+        .into());
+    }
+
+    // `Variant(__f0, __f1) => Target::with_status::<404, _>(..)`
+    let mut arms = Vec::new();
+    for variant in variants {
+        let variant_ident = &variant.ident;
+        let bindings = (0..variant.fields.len())
+            .map(|idx| format_ident!("__f{}", idx))
+            .collect::<Vec<_>>();
+        let pattern = match variant.fields.style {
+            Style::Unit => quote!(#error_ident::#variant_ident),
+            Style::Tuple => quote!(#error_ident::#variant_ident(#(#bindings),*)),
+            Style::Struct => {
+                let idents = variant.fields.iter().map(|field| &field.ident);
+                quote!(#error_ident::#variant_ident { #(#idents: #bindings),* })
+            }
+        };
+
+        let response_ty = &variant.response;
+        let response = match (&variant.with, bindings.as_slice()) {
+            (Some(with), _) => quote!(#with(#(#bindings),*)),
+            (None, []) => quote!(<#response_ty as ::std::default::Default>::default()),
+            (None, [binding]) => {
+                quote!(<#response_ty as ::std::convert::From<_>>::from(#binding))
+            }
+            (None, bindings) => {
+                quote!(<#response_ty as ::std::convert::From<_>>::from((#(#bindings),*)))
+            }
+        };
+        let status = Literal::u16_unsuffixed(variant.status.0);
+        arms.push(quote! {
+            #pattern => Self::with_status::<#status, _>(#response),
+        });
+    }
+
+    // `UserErrorResponse` for `UserError` unless `#[oai(into = "..")]` is used
+    let (alias, targets) = if args.into.is_empty() {
+        let vis = &args.vis;
+        let alias_ident = format_ident!("{}Response", error_ident);
+        let (slots, assertions) = slots(variants);
+        let slots = slots.into_iter().map(|(status, ty)| {
+            let status = Literal::u16_unsuffixed(status);
+            quote!(#status: #ty)
+        });
+        (
+            quote! {
+                #vis type #alias_ident = ::poem_extensions::response! { #(#slots),* };
+
+                #(#assertions)*
+            },
+            vec![alias_ident.to_token_stream()],
+        )
+    } else {
+        (
+            quote!(),
+            args.into.iter().map(ToTokens::to_token_stream).collect(),
+        )
+    };
+
+    let from_impls = targets.iter().map(|target| {
+        quote! {
+            impl ::std::convert::From<#error_ident> for #target {
+                fn from(err: #error_ident) -> Self {
+                    match err {
+                        #(#arms)*
+                    }
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        #alias
+        #(#from_impls)*
+    })
+}
+
+/// The status codes in the order they first appear, with their response types,
+/// and the assertions that the variants mapped to one status code have the
+/// same response type.
+fn slots(variants: &[ErrorVariant]) -> (Vec<(u16, &Type)>, Vec<TokenStream>) {
+    let mut slots: Vec<(u16, &Type)> = Vec::new();
+    let mut assertions = Vec::new();
+    for variant in variants {
+        let status = variant.status.0;
+        match slots.iter().find(|(other, _)| *other == status) {
+            Some((_, ty)) => {
+                let response_ty = &variant.response;
+                assertions.push(quote_spanned! {response_ty.span()=>
+                    const _: fn() = || {
+                        fn same_response<T: ::poem_extensions::SameResponse<U>, U>() {}
+                        same_response::<#response_ty, #ty>();
+                    };
+                });
+            }
+            None => slots.push((status, &variant.response)),
+        }
+    }
+    (slots, assertions)
+}
@@ -1,5 +1,7 @@
 mod api;
 mod error;
+mod error_response;
+mod header;
 mod one_response;
 mod response;
 mod uni_open_api;
@@ -7,10 +9,10 @@ mod uni_response;
 
 use api::Apis;
 use error::GeneratorResult;
+use header::is_header_name;
 use proc_macro::TokenStream;
-use response::Responses;
 use syn::{parse_macro_input, DeriveInput};
-use uni_response::SUPPORT_STATUS;
+use uni_response::{named_status, similar_status, SUPPORT_STATUS_RANGE, VALID_STATUS};
 
 #[proc_macro_derive(UniOpenApi)]
 pub fn derive_uni_open_api(input: TokenStream) -> TokenStream {
@@ -36,12 +38,26 @@ pub fn derive_one_response(input: TokenStream) -> TokenStream {
         .into()
 }
 
+#[proc_macro_derive(ErrorResponse, attributes(oai))]
+pub fn derive_error_response(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as DeriveInput);
+    error_response::generate(args)
+        .unwrap_or_else(|e| e.write_errors())
+        .into()
+}
+
+#[proc_macro_derive(ProblemResponse, attributes(oai))]
+pub fn derive_problem_response(input: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(input as DeriveInput);
+    one_response::generate_problem(args)
+        .unwrap_or_else(|e| e.write_errors())
+        .into()
+}
+
 #[proc_macro]
 pub fn response(input: TokenStream) -> TokenStream {
-    let args = parse_macro_input!(input as Responses);
-    response::generate(args)
-        .unwrap_or_else(|e| e.to_compile_error())
-        .into()
+    let args = parse_macro_input!(input as response::Input);
+    response::generate(args).into()
 }
 
 #[doc(hidden)]
@@ -49,3 +65,9 @@ pub fn response(input: TokenStream) -> TokenStream {
 pub fn generate_define_uni_response(_: TokenStream) -> TokenStream {
     uni_response::generate().into()
 }
+
+#[doc(hidden)]
+#[proc_macro]
+pub fn generate_slot_ids(_: TokenStream) -> TokenStream {
+    uni_response::generate_slot_ids().into()
+}
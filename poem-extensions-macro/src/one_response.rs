@@ -1,35 +1,96 @@
 use darling::{
-    ast::{Data, Fields},
-    util::{Ignored, SpannedValue},
-    FromDeriveInput, FromField, FromMeta,
+    ast::{Data, Fields, Style},
+    util::SpannedValue,
+    FromDeriveInput, FromField, FromMeta, FromVariant,
 };
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::quote;
-use syn::{Attribute, DeriveInput, Expr, ExprLit, Generics, Lit, Meta, MetaNameValue, Path, Type};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::{
+    ext::IdentExt, Attribute, DeriveInput, Expr, ExprLit, GenericArgument, Generics, Lit, LitChar,
+    LitFloat, LitInt, Meta, MetaNameValue, Path, PathArguments, Type,
+};
+
+use crate::{is_header_name, named_status, GeneratorResult, SUPPORT_STATUS_RANGE, VALID_STATUS};
+
+/// `404` or the name of a `poem::http::StatusCode` constant, e.g. `NOT_FOUND`
+#[derive(Debug)]
+pub(crate) struct StatusAttr(pub(crate) u16);
 
-use crate::{GeneratorResult, SUPPORT_STATUS};
+impl FromMeta for StatusAttr {
+    fn from_expr(expr: &Expr) -> darling::Result<Self> {
+        match expr {
+            Expr::Lit(ExprLit {
+                lit: Lit::Int(lit), ..
+            }) => Ok(StatusAttr(lit.base10_parse()?)),
+            Expr::Path(path) => match path.path.get_ident() {
+                Some(ident) => named_status(&ident.to_string())
+                    .map(StatusAttr)
+                    .map_err(|message| darling::Error::custom(message).with_span(ident)),
+                None => Err(darling::Error::unexpected_expr_type(expr)),
+            },
+            Expr::Group(group) => Self::from_expr(&group.expr),
+            _ => Err(darling::Error::unexpected_expr_type(expr)),
+        }
+    }
+}
 
 #[derive(FromMeta, Debug)]
 struct ExtraHeader {
-    name: String,
+    name: SpannedValue<String>,
 
     ty: SpannedValue<String>,
     #[darling(default)]
     description: Option<String>,
     #[darling(default)]
     deprecated: bool,
+    #[darling(default)]
+    value: Option<Lit>,
+    #[darling(default)]
+    with: Option<Path>,
 }
 
 #[derive(FromField, Debug)]
 #[darling(attributes(oai), forward_attrs(doc))]
 struct ResponseField {
+    ident: Option<Ident>,
     ty: Type,
     attrs: Vec<Attribute>,
 
     #[darling(default)]
-    header: Option<String>,
+    body: bool,
+    #[darling(default)]
+    status: bool,
+    #[darling(default)]
+    header: Option<SpannedValue<String>>,
     #[darling(default)]
     deprecated: bool,
+    #[darling(default)]
+    cookie: Option<SpannedValue<String>>,
+    #[darling(default)]
+    http_only: bool,
+    #[darling(default)]
+    secure: bool,
+    #[darling(default)]
+    same_site: Option<SpannedValue<String>>,
+    #[darling(default)]
+    max_age: Option<SpannedValue<i64>>,
+    #[darling(default)]
+    path: Option<SpannedValue<String>>,
+}
+
+#[derive(FromVariant, Debug)]
+#[darling(attributes(oai), forward_attrs(doc))]
+struct ResponseVariant {
+    ident: Ident,
+    attrs: Vec<Attribute>,
+    fields: Fields<ResponseField>,
+
+    #[darling(default)]
+    content_type: Option<String>,
+    #[darling(default)]
+    actual_type: Option<Type>,
+    #[darling(default)]
+    error_message: Option<SpannedValue<String>>,
 }
 
 #[derive(FromDeriveInput, Debug)]
@@ -38,9 +99,12 @@ struct ResponseArgs {
     ident: Ident,
     attrs: Vec<Attribute>,
     generics: Generics,
-    data: Data<Ignored, ResponseField>,
+    data: Data<ResponseVariant, ResponseField>,
 
-    status: u16,
+    #[darling(default)]
+    status: Option<StatusAttr>,
+    #[darling(default)]
+    status_range: Option<String>,
     #[darling(default)]
     bad_request_handler: Option<Path>,
     #[darling(default)]
@@ -51,66 +115,197 @@ struct ResponseArgs {
     actual_type: Option<Type>,
     #[darling(default)]
     display: bool,
+    #[darling(default)]
+    header_case: Option<HeaderCase>,
+    #[darling(default)]
+    problem: bool,
+    #[darling(default)]
+    error_message: Option<SpannedValue<String>>,
+    #[darling(default)]
+    error_message_with: Option<Path>,
+    #[darling(default)]
+    error_summary_with: Option<Path>,
+}
+
+/// `#[oai(header_case = "lower")]` normalizes the case of every header name,
+/// which is kept as declared otherwise.
+#[derive(FromMeta, Debug, Clone, Copy)]
+#[darling(rename_all = "lowercase")]
+enum HeaderCase {
+    Upper,
+    Lower,
+}
+
+impl HeaderCase {
+    fn apply(case: Option<Self>, name: String) -> String {
+        match case {
+            Some(HeaderCase::Upper) => name.to_uppercase(),
+            Some(HeaderCase::Lower) => name.to_lowercase(),
+            None => name,
+        }
+    }
 }
 
+/// The attributes of the type that apply to every struct or enum variant.
+struct TypeAttrs<'a> {
+    status: &'a Status,
+    header_case: Option<HeaderCase>,
+    problem: bool,
+}
 pub(crate) fn generate(args: DeriveInput) -> GeneratorResult<TokenStream> {
-    let args: ResponseArgs = ResponseArgs::from_derive_input(&args)?;
+    generate_response(ResponseArgs::from_derive_input(&args)?)
+}
+
+/// `#[derive(ProblemResponse)]`, a `OneResponse` with `#[oai(problem)]`
+pub(crate) fn generate_problem(args: DeriveInput) -> GeneratorResult<TokenStream> {
+    let mut args = ResponseArgs::from_derive_input(&args)?;
+    args.problem = true;
+    generate_response(args)
+}
+
+fn generate_response(args: ResponseArgs) -> GeneratorResult<TokenStream> {
     let (impl_generics, ty_generics, where_clause) = args.generics.split_for_impl();
     let struct_ident = &args.ident;
-    let status = get_status(struct_ident.span(), args.status)?;
+    let status = get_status(
+        struct_ident.span(),
+        args.status.as_ref().map(|status| status.0),
+        args.status_range.as_deref(),
+    )?;
 
-    let struct_fields = match &args.data {
-        Data::Struct(s) => s,
-        _ => {
-            return Err(syn::Error::new(
-                struct_ident.span(),
-                "Response can only be applied to an tuple struct.",
-            )
-            .into())
-        }
+    let description = get_description(&args.attrs)?;
+    let struct_description = optional_literal(&description);
+    if args.display as u8
+        + args.error_message_with.is_some() as u8
+        + args.error_message.is_some() as u8
+        > 1
+    {
+        return Err(syn::Error::new(
+            struct_ident.span(),
+            "display, error_message and error_message_with cannot be used together.",
+        )
+        .into());
+    }
+    let type_attrs = TypeAttrs {
+        status: &status,
+        header_case: args.header_case,
+        problem: args.problem,
     };
 
-    let into_response_arm;
-    let meta_response_obj;
-    let register_fn_body;
-    let error_message_arm;
-
-    let struct_description = get_description(&args.attrs)?;
-    let struct_description = optional_literal(&struct_description);
-    let (value_fields, header_fields) = parse_fields(struct_fields)?;
+    // a struct has one shape, an enum has one shape per variant
+    let shapes = match &args.data {
+        Data::Struct(fields) => vec![generate_shape(
+            quote!(#struct_ident),
+            fields,
+            match &args.error_message {
+                Some(template) => ErrorMessage::Template(template),
+                None => ErrorMessage::Description(description.clone()),
+            },
+            args.content_type.as_deref(),
+            args.actual_type.as_ref(),
+            &type_attrs,
+            struct_ident.span(),
+        )?],
+        Data::Enum(variants) => {
+            if variants.is_empty() {
+                return Err(syn::Error::new(
+                    struct_ident.span(),
+                    "Response enum must have at least one variant.",
+                )
+                .into());
+            }
+            variants
+                .iter()
+                .map(|variant| {
+                    let variant_ident = &variant.ident;
+                    let message = match variant
+                        .error_message
+                        .as_ref()
+                        .or(args.error_message.as_ref())
+                    {
+                        Some(template) => ErrorMessage::Template(template),
+                        None => ErrorMessage::Description(
+                            get_description(&variant.attrs)?.or_else(|| description.clone()),
+                        ),
+                    };
+                    if message.is_template() && (args.display || args.error_message_with.is_some())
+                    {
+                        return Err(syn::Error::new(
+                            variant_ident.span(),
+                            "error_message cannot be used with display or error_message_with.",
+                        )
+                        .into());
+                    }
+                    generate_shape(
+                        quote!(#struct_ident::#variant_ident),
+                        &variant.fields,
+                        message,
+                        variant
+                            .content_type
+                            .as_deref()
+                            .or(args.content_type.as_deref()),
+                        variant.actual_type.as_ref().or(args.actual_type.as_ref()),
+                        &type_attrs,
+                        variant_ident.span(),
+                    )
+                })
+                .collect::<GeneratorResult<Vec<_>>>()?
+        }
+    };
 
-    let mut matched_header_idents = Vec::new();
-    let mut insert_response_with_headers = Vec::new();
-    let mut meta_headers = Vec::new();
+    let into_response_arms = shapes.iter().map(|shape| &shape.into_response_arm);
+    let error_message_arms = shapes.iter().map(|shape| &shape.error_message_arm);
+    let status_arms = shapes.iter().map(|shape| &shape.status_arm);
+    let register_fn_body = shapes.iter().map(|shape| &shape.register);
 
-    // headers
-    for (idx, header_field) in header_fields.iter().enumerate() {
-        let header_ident = quote::format_ident!("__p{}", idx);
-        let header_name = header_field.header.as_ref().unwrap().to_uppercase();
-        let header_ty = &header_field.ty;
-        let header_desc = optional_literal_string(&get_description(&header_field.attrs)?);
-        let deprecated = header_field.deprecated;
+    // an enum documents the content of all its variants, with the schemas of
+    // one content type merged into `oneOf`
+    let contents = shapes.iter().map(|shape| &shape.content);
+    let meta_content = match &args.data {
+        Data::Struct(_) => quote!(#(#contents)*),
+        Data::Enum(_) => quote! {
+            {
+                let mut content = ::std::vec::Vec::new();
+                #(content.extend(#contents);)*
+                ::poem_extensions::merge_media_types(content)
+            }
+        },
+    };
 
-        insert_response_with_headers.push(quote! {
-            if let Some(header) = ::poem_openapi::types::ToHeader::to_header(&#header_ident) {
-                resp.headers_mut().insert(#header_name, header);
+    // a header that only some variants have is not required
+    let mut headers: Vec<(&str, &TokenStream, usize)> = Vec::new();
+    for shape in &shapes {
+        for (idx, (name, meta)) in shape.headers.iter().enumerate() {
+            if shape.headers[..idx].iter().any(|(other, _)| other == name) {
+                continue;
             }
-        });
-        matched_header_idents.push(header_ident);
-        meta_headers.push(quote! {
-            ::poem_openapi::registry::MetaHeader {
-                name: ::std::string::ToString::to_string(#header_name),
-                description: #header_desc,
-                required: <#header_ty as ::poem_openapi::types::Type>::IS_REQUIRED,
-                deprecated: #deprecated,
-                schema: <#header_ty as ::poem_openapi::types::Type>::schema_ref(),
+            match headers.iter_mut().find(|(other, ..)| other == name) {
+                Some((_, _, count)) => *count += 1,
+                None => headers.push((name, meta, 1)),
             }
-        });
+        }
     }
+    let mut meta_headers = headers
+        .into_iter()
+        .map(|(_, meta, count)| {
+            if count == shapes.len() {
+                meta.clone()
+            } else {
+                quote! {
+                    {
+                        let mut header = #meta;
+                        header.required = false;
+                        header
+                    }
+                }
+            }
+        })
+        .collect::<Vec<_>>();
 
     // extra headers
+    let mut insert_extra_headers = Vec::new();
     for extra_header in args.headers.iter() {
-        let name = extra_header.name.to_uppercase();
+        let name = header_name(extra_header.name.to_string(), extra_header.name.span())?;
+        let name = HeaderCase::apply(args.header_case, name);
         let description = optional_literal_string(&extra_header.description);
         let ty = match syn::parse_str::<Type>(&extra_header.ty) {
             Ok(ty) => ty,
@@ -118,6 +313,28 @@ pub(crate) fn generate(args: DeriveInput) -> GeneratorResult<TokenStream> {
         };
         let deprecated = extra_header.deprecated;
 
+        // a header with a `value` or `with` is sent by `into_response`
+        let value = match (&extra_header.value, &extra_header.with) {
+            (Some(value), None) => Some(extra_header_value(&ty, value)?),
+            (None, Some(with)) => Some(quote!(#with())),
+            (Some(value), Some(_)) => {
+                return Err(syn::Error::new(
+                    value.span(),
+                    "value and with cannot be used together.",
+                )
+                .into())
+            }
+            (None, None) => None,
+        };
+        if let Some(value) = value {
+            insert_extra_headers.push(quote! {
+                let value: #ty = #value;
+                if let Some(header) = ::poem_openapi::types::ToHeader::to_header(&value) {
+                    resp.headers_mut().insert(#name, header);
+                }
+            });
+        }
+
         meta_headers.push(quote! {
             ::poem_openapi::registry::MetaHeader {
                 name: ::std::string::ToString::to_string(#name),
@@ -129,83 +346,25 @@ pub(crate) fn generate(args: DeriveInput) -> GeneratorResult<TokenStream> {
         });
     }
 
-    match value_fields.len() {
-        1 => {
-            // Field(media)
-            let media_ty = &value_fields[0].ty;
-            let (update_response_content_type, update_meta_content_type) =
-                update_content_type(args.content_type.as_deref(), args.actual_type.as_ref());
-            into_response_arm = quote! {
-                #struct_ident(media, #(#matched_header_idents),*) => {
-                    let mut resp = ::poem::web::IntoResponse::into_response(media);
-                    resp.set_status(::poem::http::StatusCode::from_u16(#status).unwrap());
-                    #(#insert_response_with_headers)*
-                    #update_response_content_type
-                    resp
-                }
-            };
-            error_message_arm = quote! {
-                #struct_ident(media, #(#matched_header_idents),*) => #struct_description,
-            };
-            meta_response_obj = quote! {
-                ::poem_openapi::registry::MetaResponse {
-                    description: #struct_description.unwrap_or_default(),
-                    status: ::std::option::Option::Some(#status),
-                    status_range: ::std::option::Option::None,
-                    content: {
-                        let mut content = <#media_ty as ::poem_openapi::ResponseContent>::media_types();
-                        #update_meta_content_type
-                        content
-                    },
-                    headers: ::std::vec![#(#meta_headers),*],
-                }
-            };
-            if let Some(actual_type) = args.actual_type.as_ref() {
-                register_fn_body = quote! {
-                    <#actual_type as ::poem_openapi::ResponseContent>::register(registry);
-                };
-            } else {
-                register_fn_body = quote! {
-                    <#media_ty as ::poem_openapi::ResponseContent>::register(registry);
-                };
-            };
-        }
-        0 => {
-            // Field
-            let field = if !header_fields.is_empty() {
-                quote!(#struct_ident(#(#matched_header_idents),*))
-            } else {
-                quote!(#struct_ident)
-            };
-            into_response_arm = quote! {
-                #field => {
-                    let status = ::poem::http::StatusCode::from_u16(#status).unwrap();
-                    #[allow(unused_mut)]
-                    let mut resp = ::poem::web::IntoResponse::into_response(status);
-                    #(#insert_response_with_headers)*
-                    resp
-                }
-            };
-            error_message_arm = quote! {
-                #field => #struct_description,
-            };
-            meta_response_obj = quote! {
-                ::poem_openapi::registry::MetaResponse {
-                    description: #struct_description.unwrap_or_default(),
-                    status: ::std::option::Option::Some(#status),
-                    status_range: ::std::option::Option::None,
-                    content: ::std::vec![],
-                    headers: ::std::vec![#(#meta_headers),*],
-                }
-            };
-            register_fn_body = quote!();
-        }
-        _ => {
-            return Err(
-                syn::Error::new(struct_ident.span(), "Incorrect response definition.").into(),
-            )
+    let (meta_status, meta_status_range) = match &status {
+        Status::Code(status) => (
+            quote!(::std::option::Option::Some(#status)),
+            quote!(::std::option::Option::None),
+        ),
+        Status::Range { range, .. } => (
+            quote!(::std::option::Option::None),
+            quote!(::std::option::Option::Some(::std::string::ToString::to_string(#range))),
+        ),
+    };
+    let meta_response_obj = quote! {
+        ::poem_openapi::registry::MetaResponse {
+            description: #struct_description.unwrap_or_default(),
+            status: #meta_status,
+            status_range: #meta_status_range,
+            content: #meta_content,
+            headers: ::std::vec![#(#meta_headers),*],
         }
-    }
+    };
 
     let bad_request_handler_const = match &args.bad_request_handler {
         Some(_) => quote!(
@@ -223,25 +382,111 @@ pub(crate) fn generate(args: DeriveInput) -> GeneratorResult<TokenStream> {
         }
     });
 
+    // `ErrorMetadata` of the `poem::Error`, the body is summarized by its type
+    let variant = match &args.data {
+        Data::Struct(_) => quote!(::std::option::Option::None),
+        Data::Enum(variants) => {
+            let arms = variants.iter().map(|variant| {
+                let variant_ident = &variant.ident;
+                let name = variant_ident.to_string();
+                quote!(#struct_ident::#variant_ident { .. } => ::std::option::Option::Some(#name),)
+            });
+            quote!(match &resp { #(#arms)* })
+        }
+    };
+    let body_summary = match &args.error_summary_with {
+        Some(path) => quote! {
+            ::std::option::Option::Some(::std::string::ToString::to_string(&#path(&resp)))
+        },
+        None => {
+            let arms = shapes.iter().map(|shape| &shape.body_summary_arm);
+            quote!(match &resp { #(#arms)* })
+        }
+    };
+    let error_metadata = quote! {
+        let variant: ::std::option::Option<&'static str> = #variant;
+        let body: ::std::option::Option<::std::string::String> = #body_summary;
+    };
+    let error_metadata_new = quote! {
+        let metadata = ::poem_extensions::ErrorMetadata::new(
+            ::std::any::type_name::<#struct_ident #ty_generics>(),
+            variant,
+            body,
+            &resp,
+        );
+    };
+
     let error_msg = if args.display {
         quote! {
             let error_msg = ::std::option::Option::Some(::std::string::ToString::to_string(&resp));
         }
+    } else if let Some(path) = &args.error_message_with {
+        quote! {
+            let error_msg = ::std::option::Option::Some(::std::string::ToString::to_string(&#path(&resp)));
+        }
     } else {
         quote! {
-            let error_msg: ::std::option::Option<&str> = match &resp {
-                #error_message_arm
+            let error_msg: ::std::option::Option<::std::string::String> = match &resp {
+                #(#error_message_arms)*
             };
         }
     };
 
+    // a status code range has no single status code
+    let (one_response_impl, slot_key) = match &status {
+        Status::Code(status) => (
+            quote! {
+                impl #impl_generics ::poem_extensions::OneResponse for #struct_ident #ty_generics #where_clause {
+                    const STATUS: u16 = #status;
+
+                    type Key = ::poem_extensions::Status<#status>;
+                }
+            },
+            quote!(::poem_extensions::Status<#status>),
+        ),
+        Status::Range { class, .. } => (quote!(), quote!(::poem_extensions::StatusRange<#class>)),
+    };
+
+    // a status code range sends the status code of its own field
+    let slot_status = match &status {
+        Status::Code(_) => quote! {
+            fn into_slot_response(self, status: ::poem::http::StatusCode) -> ::poem::Response {
+                let mut resp = ::poem::web::IntoResponse::into_response(self);
+                resp.set_status(status);
+                resp
+            }
+        },
+        Status::Range { .. } => quote! {
+            fn into_slot_response(self, _status: ::poem::http::StatusCode) -> ::poem::Response {
+                ::poem::web::IntoResponse::into_response(self)
+            }
+
+            fn status(&self) -> ::std::option::Option<::poem::http::StatusCode> {
+                match self {
+                    #(#status_arms)*
+                }
+            }
+        },
+    };
+
+    // `IntoSlotResponse` requires `Send`
+    let mut slot_generics = args.generics.clone();
+    slot_generics
+        .make_where_clause()
+        .predicates
+        .push(syn::parse_quote!(Self: ::std::marker::Send));
+    let slot_where_clause = &slot_generics.where_clause;
+
     let expanded = {
         quote! {
             impl #impl_generics ::poem::web::IntoResponse for #struct_ident #ty_generics #where_clause {
                 fn into_response(self) -> ::poem::Response {
-                    match self {
-                        #into_response_arm
-                    }
+                    #[allow(unused_mut)]
+                    let mut resp = match self {
+                        #(#into_response_arms)*
+                    };
+                    #(#insert_extra_headers)*
+                    resp
                 }
             }
 
@@ -255,17 +500,43 @@ pub(crate) fn generate(args: DeriveInput) -> GeneratorResult<TokenStream> {
                 }
 
                 fn register(registry: &mut ::poem_openapi::registry::Registry) {
-                    #register_fn_body
+                    #(#register_fn_body)*
                 }
 
                 #bad_request_handler
             }
 
+            #one_response_impl
+
+            impl #impl_generics ::poem_extensions::Slot<#slot_key> for #struct_ident #ty_generics #where_clause {}
+
+            impl #impl_generics ::poem_extensions::IntoSlotResponse for #struct_ident #ty_generics #slot_where_clause {
+                #slot_status
+
+                fn into_slot_error(self, status: ::poem::http::StatusCode) -> ::poem::error::Error {
+                    let resp = self;
+                    #error_msg
+                    #error_metadata
+                    let resp = ::poem_extensions::IntoSlotResponse::into_slot_response(resp, status);
+                    #error_metadata_new
+                    let mut err = ::poem::error::Error::from_response(resp);
+                    err.set_data(metadata);
+                    if let ::std::option::Option::Some(error_msg) = error_msg {
+                        err.set_error_message(error_msg);
+                    }
+                    err
+                }
+            }
+
             impl #impl_generics ::std::convert::From<#struct_ident #ty_generics> for ::poem::error::Error #where_clause {
                 fn from(resp: #struct_ident #ty_generics) -> ::poem::error::Error {
                     use ::poem::web::IntoResponse;
                     #error_msg
-                    let mut err = ::poem::error::Error::from_response(resp.into_response());
+                    #error_metadata
+                    let resp = resp.into_response();
+                    #error_metadata_new
+                    let mut err = ::poem::error::Error::from_response(resp);
+                    err.set_data(metadata);
                     if let ::std::option::Option::Some(error_msg) = error_msg {
                         err.set_error_message(error_msg);
                     }
@@ -278,21 +549,675 @@ pub(crate) fn generate(args: DeriveInput) -> GeneratorResult<TokenStream> {
     Ok(expanded)
 }
 
-fn parse_fields(
+/// How one struct or enum variant is turned into a response.
+struct Shape {
+    into_response_arm: TokenStream,
+    status_arm: TokenStream,
+    error_message_arm: TokenStream,
+    body_summary_arm: TokenStream,
+    content: TokenStream,
+    register: TokenStream,
+    headers: Vec<(String, TokenStream)>,
+}
+
+/// The error message of the `poem::Error` made from one struct or enum variant.
+enum ErrorMessage<'a> {
+    /// `#[oai(error_message = "user {0.id} not found")]`
+    Template(&'a SpannedValue<String>),
+    /// The doc comment
+    Description(Option<String>),
+}
+
+impl ErrorMessage<'_> {
+    fn is_template(&self) -> bool {
+        matches!(self, ErrorMessage::Template(_))
+    }
+}
+
+fn generate_shape(
+    path: TokenStream,
+    fields: &Fields<ResponseField>,
+    message: ErrorMessage,
+    content_type: Option<&str>,
+    actual_type: Option<&Type>,
+    type_attrs: &TypeAttrs,
+    span: Span,
+) -> GeneratorResult<Shape> {
+    let roles = field_roles(fields, type_attrs, span)?;
+
+    let set_response_status = match type_attrs.status {
+        Status::Code(status) => quote! {
+            let status = ::poem::http::StatusCode::from_u16(#status).unwrap();
+        },
+        Status::Range { class, .. } => quote! {
+            if let ::std::result::Result::Err(err) =
+                <::poem_extensions::StatusRange<#class> as ::poem_extensions::SlotKey>::check(status)
+            {
+                return ::poem::error::Error::from(err).into_response();
+            }
+        },
+    };
+
+    let mut bindings = Vec::new();
+    let mut media_ty = None;
+    let mut insert_response_with_headers = Vec::new();
+    let mut headers = Vec::new();
+    let mut cookies = Vec::new();
+
+    for (idx, (field, role)) in fields.fields.iter().zip(roles).enumerate() {
+        match role {
+            Role::Status => bindings.push(quote::format_ident!("status")),
+            Role::Body => {
+                bindings.push(quote::format_ident!("media"));
+                media_ty = Some(&field.ty);
+            }
+            Role::Header(header_name) => {
+                let header_ident = quote::format_ident!("__p{}", idx);
+                let header_ty = &field.ty;
+                let header_desc = optional_literal_string(&get_description(&field.attrs)?);
+                let deprecated = field.deprecated;
+
+                // a `Vec` sends one header line per value, and none when it is empty
+                // or `None`
+                let values = if is_vec(header_ty) {
+                    Some(quote!(&#header_ident))
+                } else if option_inner(header_ty).is_some_and(is_vec) {
+                    Some(quote!(#header_ident.iter().flatten()))
+                } else {
+                    None
+                };
+                let required = if let Some(values) = values {
+                    insert_response_with_headers.push(quote! {
+                        for value in #values {
+                            if let Some(header) = ::poem_openapi::types::ToHeader::to_header(value) {
+                                resp.headers_mut().append(#header_name, header);
+                            }
+                        }
+                    });
+                    quote!(false)
+                } else {
+                    insert_response_with_headers.push(quote! {
+                        if let Some(header) = ::poem_openapi::types::ToHeader::to_header(&#header_ident) {
+                            resp.headers_mut().insert(#header_name, header);
+                        }
+                    });
+                    quote!(<#header_ty as ::poem_openapi::types::Type>::IS_REQUIRED)
+                };
+                bindings.push(header_ident);
+                let meta = quote! {
+                    ::poem_openapi::registry::MetaHeader {
+                        name: ::std::string::ToString::to_string(#header_name),
+                        description: #header_desc,
+                        required: #required,
+                        deprecated: #deprecated,
+                        schema: <#header_ty as ::poem_openapi::types::Type>::schema_ref(),
+                    }
+                };
+                headers.push((header_name, meta));
+            }
+            Role::Cookie(cookie_name, attributes) => {
+                let cookie_ident = quote::format_ident!("__p{}", idx);
+                let cookie_ty = &field.ty;
+                let mut cookie_desc = format!("`{cookie_name}`");
+                if let Some(attributes) = attributes.strip_prefix("; ") {
+                    cookie_desc += &format!(" ({attributes})");
+                }
+                if let Some(description) = get_description(&field.attrs)? {
+                    cookie_desc += &format!(": {description}");
+                }
+
+                insert_response_with_headers.push(quote! {
+                    if let Some(value) = ::poem_openapi::types::ToHeader::to_header(&#cookie_ident) {
+                        resp.headers_mut().append(
+                            ::poem::http::header::SET_COOKIE,
+                            ::poem_extensions::set_cookie(#cookie_name, &value, #attributes),
+                        );
+                    }
+                });
+                bindings.push(cookie_ident);
+                cookies.push((cookie_ty, cookie_desc));
+            }
+        }
+    }
+
+    // all cookies are documented as one `Set-Cookie` header
+    if !cookies.is_empty() {
+        let header_name = HeaderCase::apply(type_attrs.header_case, "Set-Cookie".to_string());
+        let description = cookies
+            .iter()
+            .map(|(_, description)| description.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+        let cookie_tys = cookies.iter().map(|(ty, _)| ty);
+        let meta = quote! {
+            ::poem_openapi::registry::MetaHeader {
+                name: ::std::string::ToString::to_string(#header_name),
+                description: ::std::option::Option::Some(::std::string::ToString::to_string(#description)),
+                required: false #(|| <#cookie_tys as ::poem_openapi::types::Type>::IS_REQUIRED)*,
+                deprecated: false,
+                schema: <::std::string::String as ::poem_openapi::types::Type>::schema_ref(),
+            }
+        };
+        headers.push((header_name, meta));
+    }
+
+    let pattern = match fields.style {
+        Style::Unit => quote!(#path),
+        Style::Tuple => quote!(#path(#(#bindings),*)),
+        Style::Struct => {
+            let idents = fields.fields.iter().map(|field| &field.ident);
+            quote!(#path { #(#idents: #bindings),* })
+        }
+    };
+    // the status code field of `status_range`, the other fields are ignored
+    let status_arm = {
+        let bindings = bindings.iter().map(|binding| {
+            if binding == "status" {
+                quote!(status)
+            } else {
+                quote!(_)
+            }
+        });
+        let pattern = match fields.style {
+            Style::Unit => quote!(#path),
+            Style::Tuple => quote!(#path(#(#bindings),*)),
+            Style::Struct => {
+                let idents = fields.fields.iter().map(|field| &field.ident);
+                quote!(#path { #(#idents: #bindings),* })
+            }
+        };
+        match type_attrs.status {
+            Status::Range { .. } => quote!(#pattern => ::std::option::Option::Some(*status),),
+            Status::Code(_) => quote!(#pattern => ::std::option::Option::None,),
+        }
+    };
+    let error_message_arm = match message {
+        ErrorMessage::Template(template) => error_message_template(&path, fields, template)?,
+        ErrorMessage::Description(description) => {
+            let description = optional_literal_string(&description);
+            quote!(#path { .. } => #description,)
+        }
+    };
+
+    match media_ty {
+        Some(media_ty) => {
+            // Field(media)
+            let (update_response_content_type, update_meta_content_type) =
+                update_content_type(content_type, actual_type);
+            let register_ty = actual_type.unwrap_or(media_ty);
+            // the `status` member of a `Problem` is the status code it is sent with
+            let set_problem_status = type_attrs.problem.then(|| {
+                quote! {
+                    let mut media = media;
+                    media.status = ::std::option::Option::Some(status.as_u16());
+                }
+            });
+            Ok(Shape {
+                into_response_arm: quote! {
+                    #pattern => {
+                        #set_response_status
+                        #set_problem_status
+                        let mut resp = ::poem::web::IntoResponse::into_response(media);
+                        resp.set_status(status);
+                        #(#insert_response_with_headers)*
+                        #update_response_content_type
+                        resp
+                    }
+                },
+                status_arm,
+                error_message_arm,
+                body_summary_arm: quote! {
+                    #path { .. } => ::std::option::Option::Some(
+                        ::std::string::ToString::to_string(::std::any::type_name::<#media_ty>())
+                    ),
+                },
+                content: quote! {
+                    {
+                        let mut content = <#media_ty as ::poem_openapi::ResponseContent>::media_types();
+                        #update_meta_content_type
+                        content
+                    }
+                },
+                register: quote! {
+                    <#register_ty as ::poem_openapi::ResponseContent>::register(registry);
+                },
+                headers,
+            })
+        }
+        None => {
+            // Field
+            Ok(Shape {
+                into_response_arm: quote! {
+                    #pattern => {
+                        #set_response_status
+                        #[allow(unused_mut)]
+                        let mut resp = ::poem::web::IntoResponse::into_response(status);
+                        #(#insert_response_with_headers)*
+                        resp
+                    }
+                },
+                status_arm,
+                error_message_arm,
+                body_summary_arm: quote!(#path { .. } => ::std::option::Option::None,),
+                content: quote!(::std::vec![]),
+                register: quote!(),
+                headers,
+            })
+        }
+    }
+}
+
+/// `"user {0.id} not found"` becomes
+/// `Path(__m0, ..) => Some(format!("user {} not found", __m0.id))`, a
+/// placeholder starts with the index or the name of a field.
+fn error_message_template(
+    path: &TokenStream,
     fields: &Fields<ResponseField>,
-) -> syn::Result<(Vec<&ResponseField>, Vec<&ResponseField>)> {
-    let mut value_fields = Vec::new();
-    let mut header_fields = Vec::new();
+    template: &SpannedValue<String>,
+) -> GeneratorResult<TokenStream> {
+    let error = |message: String| syn::Error::new(template.span(), message);
+
+    let mut format = String::new();
+    let mut args = Vec::new();
+    let mut used = vec![false; fields.len()];
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                format += "{{";
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                format += "}}";
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err(error("unclosed `{` in error_message".into()).into()),
+                    }
+                }
+                let (expr, spec) = match placeholder.split_once(':') {
+                    Some((expr, spec)) => (expr, Some(spec)),
+                    None => (placeholder.as_str(), None),
+                };
+                let (field, rest) = match expr.split_once('.') {
+                    Some((field, rest)) => (field, Some(rest)),
+                    None => (expr, None),
+                };
+                let idx = fields
+                    .fields
+                    .iter()
+                    .enumerate()
+                    .position(|(idx, f)| match &f.ident {
+                        Some(ident) => ident.unraw() == field.trim(),
+                        None => field.trim() == idx.to_string(),
+                    })
+                    .ok_or_else(|| {
+                        error(format!("no field `{}` in error_message", field.trim()))
+                    })?;
+                used[idx] = true;
+
+                let mut arg = format!("__m{idx}");
+                if let Some(rest) = rest {
+                    arg = format!("{arg}.{rest}");
+                }
+                let arg = syn::parse_str::<Expr>(&arg)
+                    .map_err(|_| error(format!("invalid placeholder `{{{placeholder}}}`")))?;
+                args.push(arg);
+                format += "{";
+                if let Some(spec) = spec {
+                    format += ":";
+                    format += spec;
+                }
+                format += "}";
+            }
+            '}' => return Err(error("unmatched `}` in error_message".into()).into()),
+            c => format.push(c),
+        }
+    }
+
+    let bindings = used.iter().enumerate().map(|(idx, used)| {
+        if *used {
+            let binding = quote::format_ident!("__m{}", idx);
+            quote!(#binding)
+        } else {
+            quote!(_)
+        }
+    });
+    let pattern = match fields.style {
+        Style::Unit => quote!(#path),
+        Style::Tuple => quote!(#path(#(#bindings),*)),
+        Style::Struct => {
+            let idents = fields.fields.iter().map(|field| &field.ident);
+            quote!(#path { #(#idents: #bindings),* })
+        }
+    };
+    Ok(quote! {
+        #pattern => ::std::option::Option::Some(::std::format!(#format, #(#args),*)),
+    })
+}
+
+/// What a field of a response is sent as.
+enum Role {
+    Status,
+    Body,
+    Header(String),
+    /// The name and the attributes, e.g. `"; HttpOnly; Path=/"`
+    Cookie(String, String),
+}
+
+/// A tuple field without `#[oai(header = ...)]` is the body, or the status code
+/// when it is the first one and `status_range` is used. A named field is a
+/// header named after the field unless it is marked `#[oai(body)]`,
+/// `#[oai(status)]` or `#[oai(cookie = ...)]`.
+fn field_roles(
+    fields: &Fields<ResponseField>,
+    type_attrs: &TypeAttrs,
+    span: Span,
+) -> GeneratorResult<Vec<Role>> {
+    let status = type_attrs.status;
+    let named = fields.style.is_struct();
+    let mut roles = Vec::new();
+    let has_status_field = fields.fields.iter().any(|field| field.status);
 
     for field in &fields.fields {
-        if field.header.is_some() {
-            header_fields.push(field);
+        let field_span = field.ident.as_ref().map_or(span, Ident::span);
+        if field.body as u8
+            + field.status as u8
+            + field.header.is_some() as u8
+            + field.cookie.is_some() as u8
+            > 1
+        {
+            return Err(syn::Error::new(
+                field_span,
+                "A field can only be one of `body`, `status`, `header` or `cookie`.",
+            )
+            .into());
+        }
+
+        let role = if let Some(cookie) = &field.cookie {
+            let (name, attributes) = cookie_attributes(field, cookie)?;
+            Role::Cookie(name, attributes)
+        } else if field.http_only
+            || field.secure
+            || field.same_site.is_some()
+            || field.max_age.is_some()
+            || field.path.is_some()
+        {
+            return Err(syn::Error::new(
+                field_span,
+                "`http_only`, `secure`, `same_site`, `max_age` and `path` can only be used with `cookie`.",
+            )
+            .into());
+        } else if let Some(header) = &field.header {
+            let name = header_name(header.to_string(), header.span())?;
+            Role::Header(HeaderCase::apply(type_attrs.header_case, name))
+        } else if field.status {
+            Role::Status
+        } else if field.body {
+            Role::Body
+        } else if named {
+            // `x_request_id` is sent as `X-Request-Id`
+            let ident = field.ident.as_ref().unwrap().unraw().to_string();
+            let name = ident
+                .split('_')
+                .map(|word| {
+                    let mut chars = word.chars();
+                    chars.next().map_or_else(String::new, |first| {
+                        first.to_uppercase().chain(chars).collect()
+                    })
+                })
+                .collect::<Vec<_>>()
+                .join("-");
+            let name = header_name(name, field_span)?;
+            Role::Header(HeaderCase::apply(type_attrs.header_case, name))
+        } else if matches!(status, Status::Range { .. })
+            && !has_status_field
+            && !roles
+                .iter()
+                .any(|role| matches!(role, Role::Status | Role::Body))
+        {
+            // Field(StatusCode, ...) when using `status_range`
+            Role::Status
+        } else {
+            Role::Body
+        };
+        roles.push(role);
+    }
+
+    let status_fields = roles
+        .iter()
+        .filter(|role| matches!(role, Role::Status))
+        .count();
+    let body_fields = roles
+        .iter()
+        .filter(|role| matches!(role, Role::Body))
+        .count();
+    match status {
+        Status::Range { .. } if status_fields == 0 => {
+            let message = if named {
+                "A field marked `#[oai(status)]` is required when using `status_range`."
+            } else {
+                "The first field must be the status code when using `status_range`."
+            };
+            return Err(syn::Error::new(span, message).into());
+        }
+        Status::Code(_) if status_fields > 0 => {
+            return Err(syn::Error::new(
+                span,
+                "A `status` field can only be used with `status_range`.",
+            )
+            .into());
+        }
+        _ if status_fields > 1 => {
+            return Err(syn::Error::new(span, "Only one field can be the status code.").into());
+        }
+        _ => {}
+    }
+    if body_fields > 1 {
+        let message = if named {
+            "Only one field can be marked `#[oai(body)]`."
         } else {
-            value_fields.push(field);
+            "Incorrect response definition."
+        };
+        return Err(syn::Error::new(span, message).into());
+    }
+
+    Ok(roles)
+}
+
+/// `session` and `"; HttpOnly; Secure; SameSite=Strict; Max-Age=3600; Path=/"`
+fn cookie_attributes(
+    field: &ResponseField,
+    cookie: &SpannedValue<String>,
+) -> GeneratorResult<(String, String)> {
+    // a cookie name is a `token`, as a header name
+    if !is_header_name(cookie) {
+        return Err(syn::Error::new(
+            cookie.span(),
+            format!("`{}` is not a valid cookie name", cookie.as_str()),
+        )
+        .into());
+    }
+
+    let mut attributes = String::new();
+    if field.http_only {
+        attributes += "; HttpOnly";
+    }
+    if field.secure {
+        attributes += "; Secure";
+    }
+    if let Some(same_site) = &field.same_site {
+        let value = match same_site.as_str() {
+            "strict" | "Strict" => "Strict",
+            "lax" | "Lax" => "Lax",
+            "none" | "None" if field.secure => "None",
+            "none" | "None" => {
+                return Err(syn::Error::new(
+                    same_site.span(),
+                    "`same_site = \"none\"` requires `secure`.",
+                )
+                .into())
+            }
+            _ => {
+                return Err(syn::Error::new(
+                    same_site.span(),
+                    "Invalid same_site, you may only use strict, lax or none.",
+                )
+                .into())
+            }
+        };
+        attributes += "; SameSite=";
+        attributes += value;
+    }
+    if let Some(max_age) = &field.max_age {
+        if **max_age < 0 {
+            return Err(syn::Error::new(max_age.span(), "`max_age` cannot be negative.").into());
+        }
+        attributes += &format!("; Max-Age={}", **max_age);
+    }
+    if let Some(path) = &field.path {
+        if path.is_empty()
+            || path
+                .bytes()
+                .any(|b| b == b';' || !(0x20..0x7F).contains(&b))
+        {
+            return Err(syn::Error::new(path.span(), "Invalid cookie path.").into());
         }
+        attributes += "; Path=";
+        attributes += path;
     }
 
-    Ok((value_fields, header_fields))
+    Ok((cookie.to_string(), attributes))
+}
+
+fn is_vec(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Vec"),
+        Type::Group(group) => is_vec(&group.elem),
+        _ => false,
+    }
+}
+
+/// `T` of `Option<T>`
+fn option_inner(ty: &Type) -> Option<&Type> {
+    match ty {
+        Type::Path(path) => {
+            let segment = path.path.segments.last()?;
+            if segment.ident != "Option" {
+                return None;
+            }
+            match &segment.arguments {
+                PathArguments::AngleBracketed(args) => match args.args.first()? {
+                    GenericArgument::Type(ty) if args.args.len() == 1 => Some(ty),
+                    _ => None,
+                },
+                _ => None,
+            }
+        }
+        Type::Group(group) => option_inner(&group.elem),
+        _ => None,
+    }
+}
+
+/// The `value` of an extra header of type `ty`. It is wrapped in `Some` for an
+/// `Option`, a string literal is parsed at compile time for a primitive type,
+/// e.g. `"100"` is `100i32` for `i32`, and converted with `From` otherwise.
+fn extra_header_value(ty: &Type, value: &Lit) -> GeneratorResult<TokenStream> {
+    if let Some(inner) = option_inner(ty) {
+        let value = extra_header_value(inner, value)?;
+        return Ok(quote!(::std::option::Option::Some(#value)));
+    }
+    let Lit::Str(lit) = value else {
+        return Ok(quote!(#value));
+    };
+    let Some(primitive) = primitive_name(ty) else {
+        return Ok(quote_spanned!(lit.span()=> ::std::convert::From::from(#lit)));
+    };
+
+    let text = lit.value();
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.as_str()),
+    };
+    let value = match primitive.as_str() {
+        "bool" => match text.as_str() {
+            "true" => Some(quote!(true)),
+            "false" => Some(quote!(false)),
+            _ => None,
+        },
+        "char" => {
+            let mut chars = text.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Some(LitChar::new(c, lit.span()).to_token_stream()),
+                _ => None,
+            }
+        }
+        "f32" | "f64" => digits
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite() && !digits.starts_with(['+', '-']))
+            .map(|value| LitFloat::new(&format!("{value:?}{primitive}"), lit.span()))
+            .map(|value| {
+                if negative {
+                    quote!(-#value)
+                } else {
+                    quote!(#value)
+                }
+            }),
+        _ => digits
+            .parse::<u128>()
+            .ok()
+            .filter(|_| !digits.starts_with('+'))
+            .map(|value| LitInt::new(&format!("{value}{primitive}"), lit.span()))
+            .map(|value| {
+                if negative {
+                    quote!(-#value)
+                } else {
+                    quote!(#value)
+                }
+            }),
+    };
+    value.ok_or_else(|| {
+        syn::Error::new(
+            lit.span(),
+            format!("`{text}` is not a valid value of `{primitive}`"),
+        )
+        .into()
+    })
+}
+
+/// The name of `ty` if it is `bool`, `char` or a number type.
+fn primitive_name(ty: &Type) -> Option<String> {
+    const PRIMITIVES: [&str; 16] = [
+        "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16",
+        "u32", "u64", "u128", "usize",
+    ];
+
+    match ty {
+        Type::Path(path) if path.qself.is_none() => {
+            let ident = path.path.get_ident()?.to_string();
+            PRIMITIVES.contains(&ident.as_str()).then_some(ident)
+        }
+        Type::Group(group) => primitive_name(&group.elem),
+        _ => None,
+    }
+}
+
+fn header_name(name: String, span: Span) -> GeneratorResult<String> {
+    if is_header_name(&name) {
+        Ok(name)
+    } else {
+        Err(syn::Error::new(span, format!("`{name}` is not a valid header name")).into())
+    }
 }
 
 fn get_description(attrs: &[Attribute]) -> syn::Result<Option<String>> {
@@ -382,13 +1307,48 @@ fn update_content_type(
     (update_response_content_type, update_meta_content_type)
 }
 
-fn get_status(span: Span, status: u16) -> GeneratorResult<TokenStream> {
-    if !SUPPORT_STATUS.contains(&status) {
-        return Err(syn::Error::new(
-            span,
-            format!("Invalid status code, support status code: {SUPPORT_STATUS:?}"),
-        )
-        .into());
+enum Status {
+    Code(u16),
+    Range { class: u16, range: String },
+}
+
+fn get_status(
+    span: Span,
+    status: Option<u16>,
+    status_range: Option<&str>,
+) -> GeneratorResult<Status> {
+    match (status, status_range) {
+        (Some(status), None) => {
+            if !VALID_STATUS.contains(&status) {
+                return Err(syn::Error::new(
+                    span,
+                    format!(
+                        "Invalid status code, expected a status code between {} and {}",
+                        VALID_STATUS.start(),
+                        VALID_STATUS.end()
+                    ),
+                )
+                .into());
+            }
+            Ok(Status::Code(status))
+        }
+        (None, Some(status_range)) => match SUPPORT_STATUS_RANGE
+            .iter()
+            .find(|class| format!("{class}XX") == status_range)
+        {
+            Some(class) => Ok(Status::Range {
+                class: *class,
+                range: status_range.to_string(),
+            }),
+            None => Err(syn::Error::new(
+                span,
+                "Invalid status range, you may only use 1XX, 2XX, 3XX, 4XX, or 5XX.",
+            )
+            .into()),
+        },
+        (Some(_), Some(_)) => {
+            Err(syn::Error::new(span, "status and status_range cannot be used together.").into())
+        }
+        (None, None) => Err(syn::Error::new(span, "Missing status attribute").into()),
     }
-    Ok(quote!(#status))
 }
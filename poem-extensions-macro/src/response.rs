@@ -1,28 +1,244 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use proc_macro2::TokenStream;
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote, ToTokens};
 use syn::{
+    braced,
+    ext::IdentExt,
     parse::{Parse, ParseStream},
     punctuated::Punctuated,
     spanned::Spanned,
-    LitInt, Token, Type,
+    token, Attribute, Ident, LitInt, LitStr, Token, Type, Visibility,
 };
 
-use crate::SUPPORT_STATUS;
+use crate::{is_header_name, named_status, similar_status, SUPPORT_STATUS_RANGE, VALID_STATUS};
+
+/// A response type, or `type Name = { .. };` items whose descriptions and
+/// headers are kept in marker types
+pub(crate) enum Input {
+    Type(Responses),
+    Items(Vec<Item>),
+}
+
+impl Parse for Input {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let fork = input.fork();
+        fork.call(Attribute::parse_outer)?;
+        fork.parse::<Visibility>()?;
+        if !fork.peek(Token![type]) {
+            return input.parse().map(Input::Type);
+        }
+
+        let mut items = Vec::new();
+        while !input.is_empty() {
+            items.push(input.parse()?);
+        }
+        Ok(Input::Items(items))
+    }
+}
+
+/// `pub type GetUser = { 200: Json<User>, 404: Json<ErrorBody> => "user not found" };`
+pub(crate) struct Item {
+    attrs: Vec<Attribute>,
+    vis: Visibility,
+    type_token: Token![type],
+    ident: Ident,
+    eq_token: Token![=],
+    brace_token: token::Brace,
+    responses: Responses,
+    semi_token: Token![;],
+}
+
+impl Parse for Item {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        Ok(Self {
+            attrs: input.call(Attribute::parse_outer)?,
+            vis: input.parse()?,
+            type_token: input.parse()?,
+            ident: input.parse()?,
+            eq_token: input.parse()?,
+            brace_token: braced!(content in input),
+            responses: content.parse()?,
+            semi_token: input.parse()?,
+        })
+    }
+}
+
+impl ToTokens for Item {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(self.attrs.iter().map(ToTokens::to_token_stream));
+        self.vis.to_tokens(tokens);
+        self.type_token.to_tokens(tokens);
+        self.ident.to_tokens(tokens);
+        self.eq_token.to_tokens(tokens);
+        self.brace_token.surround(tokens, |tokens| {
+            self.responses.to_tokens(tokens);
+        });
+        self.semi_token.to_tokens(tokens);
+    }
+}
 
 pub(crate) struct Responses {
-    responses: Punctuated<Response, Token![,]>,
+    responses: Punctuated<Entry, Token![,]>,
 }
 
 impl Parse for Responses {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         Ok(Responses {
-            responses: input.parse_terminated(Response::parse, Token![,])?,
+            responses: input.parse_terminated(Entry::parse, Token![,])?,
         })
     }
 }
 
+/// `200: A`, `..CommonErrors` or `headers { .. }`
+enum Entry {
+    Response(Response),
+    Spread(Spread),
+    Headers(HeaderBlock),
+}
+
+impl Parse for Entry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![..]) {
+            input.parse().map(Entry::Spread)
+        } else if input.peek(kw::headers) && input.peek2(token::Brace) {
+            input.parse().map(Entry::Headers)
+        } else {
+            input.parse().map(Entry::Response)
+        }
+    }
+}
+
+impl ToTokens for Entry {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Entry::Response(response) => response.to_tokens(tokens),
+            Entry::Spread(spread) => spread.to_tokens(tokens),
+            Entry::Headers(headers) => headers.to_tokens(tokens),
+        }
+    }
+}
+
+/// `headers { "X-Request-Id": String => "request id", .. }`, documented for
+/// every status code
+struct HeaderBlock {
+    headers_token: kw::headers,
+    brace_token: token::Brace,
+    headers: Punctuated<HeaderEntry, Token![,]>,
+}
+
+impl Parse for HeaderBlock {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        Ok(Self {
+            headers_token: input.parse()?,
+            brace_token: braced!(content in input),
+            headers: content.parse_terminated(HeaderEntry::parse, Token![,])?,
+        })
+    }
+}
+
+impl ToTokens for HeaderBlock {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.headers_token.to_tokens(tokens);
+        self.brace_token.surround(tokens, |tokens| {
+            self.headers.to_tokens(tokens);
+        });
+    }
+}
+
+/// `"X-Request-Id": String => "request id"`
+struct HeaderEntry {
+    name: LitStr,
+    colon_token: Token![:],
+    ty: Type,
+    description: Option<(Token![=>], LitStr)>,
+}
+
+impl Parse for HeaderEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            name: input.parse()?,
+            colon_token: input.parse()?,
+            ty: input.parse()?,
+            description: if input.peek(Token![=>]) {
+                Some((input.parse()?, input.parse()?))
+            } else {
+                None
+            },
+        })
+    }
+}
+
+impl ToTokens for HeaderEntry {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.name.to_tokens(tokens);
+        self.colon_token.to_tokens(tokens);
+        self.ty.to_tokens(tokens);
+        if let Some((fat_arrow_token, description)) = &self.description {
+            fat_arrow_token.to_tokens(tokens);
+            description.to_tokens(tokens);
+        }
+    }
+}
+
+impl HeaderBlock {
+    /// Pushes an error for every invalid header name, and for every header
+    /// name that is declared again, ignoring case.
+    fn check(&self, errors: &mut Vec<syn::Error>) {
+        let mut names = Vec::<(String, &LitStr)>::new();
+        for header in self.headers.iter() {
+            let name = header.name.value();
+            if !is_header_name(&name) {
+                errors.push(syn::Error::new(header.name.span(), "invalid header name"));
+                continue;
+            }
+            let name = name.to_ascii_lowercase();
+            match names.iter().find(|(other, _)| *other == name) {
+                Some((_, first)) => errors.push(duplicate_error(
+                    &header.name,
+                    *first,
+                    &format!("header `{}`", header.name.value()),
+                )),
+                None => names.push((name, &header.name)),
+            }
+        }
+    }
+
+    /// `WithHeaders<(Header<__GetUser_header_0, T>,), R>`
+    fn wrap(&self, response: TokenStream, markers: &mut Markers) -> TokenStream {
+        let headers = self.headers.iter().enumerate().map(|(idx, header)| {
+            let marker = markers.header(idx, header);
+            let ty = &header.ty;
+            quote!(::poem_extensions::Header<#marker, #ty>)
+        });
+        quote!(::poem_extensions::WithHeaders<(#(#headers,)*), #response>)
+    }
+}
+
+/// `..CommonErrors`, where `CommonErrors` is a `ResponseSet`
+struct Spread {
+    dot2_token: Token![..],
+    set: Type,
+}
+
+impl Parse for Spread {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            dot2_token: input.parse()?,
+            set: input.parse()?,
+        })
+    }
+}
+
+impl ToTokens for Spread {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.dot2_token.to_tokens(tokens);
+        self.set.to_tokens(tokens);
+    }
+}
+
 impl ToTokens for Responses {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         self.responses.to_tokens(tokens);
@@ -30,72 +246,447 @@ impl ToTokens for Responses {
 }
 
 struct Response {
-    status_code: u16,
+    status: ResponseStatus,
     colon_token: Token![:],
-    response_type: Type,
+    /// `Json<T> | Xml<T>`
+    response_types: Punctuated<Type, Token![|]>,
+    description: Option<Description>,
 }
 
 impl Parse for Response {
     fn parse(input: ParseStream) -> syn::Result<Self> {
         Ok(Self {
-            status_code: input.parse::<LitInt>()?.base10_parse::<u16>()?,
+            status: input.parse()?,
             colon_token: input.parse()?,
-            response_type: input.parse()?,
+            response_types: Punctuated::parse_separated_nonempty(input)?,
+            description: if input.peek(Token![=>]) {
+                Some(input.parse()?)
+            } else {
+                None
+            },
         })
     }
 }
 
 impl ToTokens for Response {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        self.status_code.to_tokens(tokens);
+        self.status.to_tokens(tokens);
         self.colon_token.to_tokens(tokens);
-        self.response_type.to_tokens(tokens);
+        self.response_types.to_tokens(tokens);
+        self.description.to_tokens(tokens);
     }
 }
 
-pub(crate) fn generate(args: Responses) -> syn::Result<TokenStream> {
-    let (status_to_type, unsupport_status): (HashMap<_, _>, HashMap<_, _>) = args
-        .responses
-        .iter()
-        .map(|r| {
-            let Response {
-                status_code,
-                response_type,
-                ..
-            } = r;
+mod kw {
+    syn::custom_keyword!(example);
+    syn::custom_keyword!(headers);
+}
+
+/// `=> "user not found"`, optionally followed by
+/// `, example = r#"{"message": "user not found"}"#`
+struct Description {
+    fat_arrow_token: Token![=>],
+    text: LitStr,
+    example: Option<Example>,
+}
 
-            (status_code, response_type)
+impl Parse for Description {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        Ok(Self {
+            fat_arrow_token: input.parse()?,
+            text: input.parse()?,
+            // `example` is never a status code
+            example: if input.peek(Token![,]) && input.peek2(kw::example) {
+                Some(input.parse()?)
+            } else {
+                None
+            },
         })
-        .partition(|(s, _)| SUPPORT_STATUS.contains(s));
-
-    if !unsupport_status.is_empty() {
-        return Err(syn::Error::new(
-            args.span(),
-            format!(
-                "\n  support status code: {SUPPORT_STATUS:?}\nunsupport status code: {:?}",
-                unsupport_status.keys(),
-            ),
-        ));
-    }
-
-    let response_types = SUPPORT_STATUS
-        .iter()
-        .map(|status| match status_to_type.get(status) {
-            Some(response_type) => {
-                quote!(#response_type)
-            }
-            None => {
-                quote!(::poem_extensions::Empty)
+    }
+}
+
+impl ToTokens for Description {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.fat_arrow_token.to_tokens(tokens);
+        self.text.to_tokens(tokens);
+        self.example.to_tokens(tokens);
+    }
+}
+
+/// `, example = r#"{"message": "user not found"}"#`, a JSON value
+struct Example {
+    comma_token: Token![,],
+    example_token: kw::example,
+    eq_token: Token![=],
+    value: LitStr,
+}
+
+impl Parse for Example {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let example = Self {
+            comma_token: input.parse()?,
+            example_token: input.parse()?,
+            eq_token: input.parse()?,
+            value: input.parse()?,
+        };
+        if let Err(err) = serde_json::from_str::<serde_json::Value>(&example.value.value()) {
+            return Err(syn::Error::new(
+                example.value.span(),
+                format!("the example is not valid JSON: {err}"),
+            ));
+        }
+        Ok(example)
+    }
+}
+
+impl ToTokens for Example {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.comma_token.to_tokens(tokens);
+        self.example_token.to_tokens(tokens);
+        self.eq_token.to_tokens(tokens);
+        self.value.to_tokens(tokens);
+    }
+}
+
+/// The marker types of a `type Name = { .. };` item, they carry the
+/// descriptions, examples and header names as associated constants.
+struct Markers<'a> {
+    vis: &'a Visibility,
+    ident: &'a Ident,
+    items: Vec<TokenStream>,
+}
+
+impl Markers<'_> {
+    /// `__GetUser_404`, a unit struct that implements `marker_trait`
+    fn marker(&mut self, suffix: &str, marker_trait: TokenStream, consts: TokenStream) -> Ident {
+        let vis = self.vis;
+        let marker = format_ident!("__{}_{}", self.ident, suffix, span = self.ident.span());
+        self.items.push(quote! {
+            #[doc(hidden)]
+            #[allow(non_camel_case_types)]
+            #vis struct #marker;
+
+            impl #marker_trait for #marker {
+                #consts
             }
         });
+        marker
+    }
+
+    fn description(&mut self, suffix: &str, description: &Description) -> Ident {
+        let text = &description.text;
+        let example = match &description.example {
+            Some(example) => {
+                let value = &example.value;
+                quote!(::std::option::Option::Some(#value))
+            }
+            None => quote!(::std::option::Option::None),
+        };
+        self.marker(
+            suffix,
+            quote!(::poem_extensions::Description),
+            quote! {
+                const DESCRIPTION: &'static str = #text;
+                const EXAMPLE: ::std::option::Option<&'static str> = #example;
+            },
+        )
+    }
+
+    fn header(&mut self, idx: usize, header: &HeaderEntry) -> Ident {
+        let name = &header.name;
+        let description = match &header.description {
+            Some((_, description)) => quote!(::std::option::Option::Some(#description)),
+            None => quote!(::std::option::Option::None),
+        };
+        self.marker(
+            &format!("header_{idx}"),
+            quote!(::poem_extensions::HeaderSpec),
+            quote! {
+                const NAME: &'static str = #name;
+                const DESCRIPTION: ::std::option::Option<&'static str> = #description;
+            },
+        )
+    }
+}
+
+impl Response {
+    /// The slot key and the response type, a described slot key is
+    /// `Described<K, __GetUser_404>`
+    fn slot(
+        &self,
+        key: TokenStream,
+        suffix: &str,
+        markers: &mut Option<Markers>,
+    ) -> (TokenStream, TokenStream) {
+        let key = match (&self.description, markers) {
+            (Some(description), Some(markers)) => {
+                let marker = markers.description(suffix, description);
+                quote!(::poem_extensions::Described<#key, #marker>)
+            }
+            _ => key,
+        };
+        (key, self.response_type())
+    }
+
+    /// `A | B | C` is `Either<A, Either<B, C>>`
+    fn response_type(&self) -> TokenStream {
+        let mut response_types = self.response_types.iter().rev();
+        let last = response_types.next().unwrap();
+        response_types.fold(
+            quote!(#last),
+            |right, left| quote!(::poem_extensions::Either<#left, #right>),
+        )
+    }
+}
+
+/// `200`, `NOT_FOUND`, `4XX` or `default`
+enum ResponseStatus {
+    Status(LitInt),
+    /// The name of a `poem::http::StatusCode` constant
+    Named(Ident),
+    StatusRange(LitInt),
+    Default(Ident),
+}
+
+impl Parse for ResponseStatus {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Ident::peek_any) {
+            let ident = input.call(Ident::parse_any)?;
+            if ident == "default" {
+                return Ok(ResponseStatus::Default(ident));
+            }
+            return Ok(ResponseStatus::Named(ident));
+        }
+
+        let lit = input.parse::<LitInt>()?;
+        match lit.suffix() {
+            "" => Ok(ResponseStatus::Status(lit)),
+            "XX" => Ok(ResponseStatus::StatusRange(lit)),
+            _ => Err(syn::Error::new(
+                lit.span(),
+                "invalid status code range, you may only use 1XX, 2XX, 3XX, 4XX, or 5XX",
+            )),
+        }
+    }
+}
+
+impl ToTokens for ResponseStatus {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            ResponseStatus::Status(lit) | ResponseStatus::StatusRange(lit) => lit.to_tokens(tokens),
+            ResponseStatus::Named(ident) | ResponseStatus::Default(ident) => {
+                ident.to_tokens(tokens)
+            }
+        }
+    }
+}
+
+pub(crate) fn generate(input: Input) -> TokenStream {
+    match input {
+        Input::Type(responses) => {
+            generate_type(&responses, &mut None).unwrap_or_else(to_compile_errors)
+        }
+        Input::Items(items) => items.iter().map(generate_item).collect(),
+    }
+}
+
+/// `type GetUser = ..;` followed by its marker types
+fn generate_item(item: &Item) -> TokenStream {
+    let Item {
+        attrs, vis, ident, ..
+    } = item;
+    let mut markers = Some(Markers {
+        vis,
+        ident,
+        items: Vec::new(),
+    });
+    let ty = generate_type(&item.responses, &mut markers).unwrap_or_else(to_compile_errors);
+    let markers = markers.map(|markers| markers.items).unwrap_or_default();
+
+    quote! {
+        #(#attrs)*
+        #vis type #ident = #ty;
+
+        #(#markers)*
+    }
+}
+
+fn generate_type(args: &Responses, markers: &mut Option<Markers>) -> syn::Result<TokenStream> {
+    let mut status_to_type = BTreeMap::new();
+    let mut status_range_to_type = BTreeMap::new();
+    let mut default_type = None;
+    let mut sets = Vec::new();
+    let mut header_block = None;
+    let mut errors = Vec::new();
+
+    for entry in args.responses.iter() {
+        let response = match entry {
+            Entry::Response(response) => response,
+            Entry::Spread(spread) => {
+                sets.push(&spread.set);
+                continue;
+            }
+            Entry::Headers(block) => {
+                if markers.is_none() {
+                    errors.push(unnamed_error(&block.headers_token, "headers"));
+                }
+                block.check(&mut errors);
+                if let Some(first) = header_block.replace(block) {
+                    errors.push(duplicate_error(
+                        &block.headers_token,
+                        &first.headers_token,
+                        "`headers` block",
+                    ));
+                }
+                continue;
+            }
+        };
+        if let (Some(description), None) = (&response.description, &markers) {
+            errors.push(unnamed_error(&description.fat_arrow_token, "a description"));
+        }
+        match &response.status {
+            ResponseStatus::Status(lit) => {
+                let status = lit.base10_parse::<u16>().ok();
+                let Some(status) = status.filter(|status| VALID_STATUS.contains(status)) else {
+                    let mut message = format!(
+                        "unsupported status code `{}`, expected a status code between {} and {}",
+                        lit.base10_digits(),
+                        VALID_STATUS.start(),
+                        VALID_STATUS.end()
+                    );
+                    if let Some(similar) = status.and_then(similar_status) {
+                        message.push_str(&format!(", did you mean {similar}?"));
+                    }
+                    errors.push(syn::Error::new(lit.span(), message));
+                    continue;
+                };
+                if let Some((first, _)) =
+                    status_to_type.insert(status, (lit as &dyn Spanned, response))
+                {
+                    errors.push(duplicate_error(
+                        lit,
+                        first,
+                        &format!("status code `{status}`"),
+                    ));
+                }
+            }
+            ResponseStatus::Named(ident) => {
+                let status = match named_status(&ident.to_string()) {
+                    Ok(status) => status,
+                    Err(message) => {
+                        errors.push(syn::Error::new(ident.span(), message));
+                        continue;
+                    }
+                };
+                if let Some((first, _)) =
+                    status_to_type.insert(status, (ident as &dyn Spanned, response))
+                {
+                    errors.push(duplicate_error(
+                        ident,
+                        first,
+                        &format!("status code `{status}`"),
+                    ));
+                }
+            }
+            ResponseStatus::StatusRange(lit) => {
+                let class = match lit.base10_parse::<u16>() {
+                    Ok(class) => class,
+                    Err(err) => {
+                        errors.push(err);
+                        continue;
+                    }
+                };
+                if !SUPPORT_STATUS_RANGE.contains(&class) {
+                    errors.push(syn::Error::new(
+                        lit.span(),
+                        "invalid status code range, you may only use 1XX, 2XX, 3XX, 4XX, or 5XX",
+                    ));
+                    continue;
+                }
+                if let Some((first, _)) = status_range_to_type.insert(class, (lit, response)) {
+                    errors.push(duplicate_error(
+                        lit,
+                        first,
+                        &format!("status code range `{class}XX`"),
+                    ));
+                }
+            }
+            ResponseStatus::Default(ident) => {
+                if let Some((first, _)) = default_type.replace((ident, response)) {
+                    errors.push(duplicate_error(ident, first, "`default`"));
+                }
+            }
+        }
+    }
+
+    if let Some(error) = errors.into_iter().reduce(|mut error, other| {
+        error.combine(other);
+        error
+    }) {
+        return Err(error);
+    }
+
+    let mut slots = Vec::new();
+    for (status, (_, response)) in status_to_type {
+        let key = quote!(::poem_extensions::Status<#status>);
+        slots.push(response.slot(key, &status.to_string(), markers));
+    }
+    for (class, (_, response)) in status_range_to_type {
+        let key = quote!(::poem_extensions::StatusRange<#class>);
+        slots.push(response.slot(key, &format!("{class}XX"), markers));
+    }
+    if let Some((_, response)) = default_type {
+        let key = quote!(::poem_extensions::DefaultStatus);
+        slots.push(response.slot(key, "default", markers));
+    }
 
-    let expand = quote! {
-        ::poem_extensions::UniResponse<
-            #(
-                #response_types,
-            )*
-        >
+    // the spread sets follow the slots, duplicate slots are rejected by the
+    // `ApiResponse` implementation of `UniResponse` at compile time
+    let tail = match sets.split_last() {
+        Some((last, sets)) => sets.iter().rev().fold(
+            quote!(#last),
+            |next, set| quote!(<#set as ::poem_extensions::ResponseSet>::Concat<#next>),
+        ),
+        None => quote!(::poem_extensions::Empty),
     };
 
-    Ok(expand)
+    // UniResponse<Status<200>, A, UniResponse<Status<404>, B>>
+    let expand = slots.into_iter().rev().fold(
+        tail,
+        |next, (key, response_type)| {
+            quote!(::poem_extensions::UniResponse<#key, #response_type, #next>)
+        },
+    );
+
+    match (header_block, markers) {
+        (Some(header_block), Some(markers)) => Ok(header_block.wrap(expand, markers)),
+        _ => Ok(expand),
+    }
+}
+
+/// `what` needs the marker types of a named response.
+fn unnamed_error(span: &dyn Spanned, what: &str) -> syn::Error {
+    syn::Error::new(
+        span.span(),
+        format!(
+            "{what} can only be declared in a named response, \
+             e.g. `response! {{ type GetUser = {{ .. }}; }}`"
+        ),
+    )
+}
+
+/// `what` is declared at both `first` and `duplicate`.
+fn duplicate_error(duplicate: &dyn Spanned, first: &dyn Spanned, what: &str) -> syn::Error {
+    let mut error = syn::Error::new(duplicate.span(), format!("duplicate {what}"));
+    error.combine(syn::Error::new(
+        first.span(),
+        format!("{what} is first declared here"),
+    ));
+    error
+}
+
+/// Several `compile_error!` are only allowed in type position as a tuple.
+fn to_compile_errors(error: syn::Error) -> TokenStream {
+    let errors = error.into_iter().map(|error| error.to_compile_error());
+    quote!((#(#errors,)*))
 }
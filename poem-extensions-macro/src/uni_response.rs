@@ -1,4 +1,6 @@
-use proc_macro2::{Ident, TokenStream};
+use std::ops::RangeInclusive;
+
+use proc_macro2::TokenStream;
 use quote::{format_ident, quote};
 
 pub(crate) const SUPPORT_STATUS: [u16; 60] = [
@@ -10,97 +12,244 @@ pub(crate) const SUPPORT_STATUS: [u16; 60] = [
     500, 501, 502, 503, 504, 505, 506, 507, 508, 510, 511, // 5xx
 ];
 
-pub(crate) fn generate() -> TokenStream {
-    let (generics, status): (Vec<Ident>, Vec<TokenStream>) = SUPPORT_STATUS
+/// The names of the `poem::http::StatusCode` constants.
+pub(crate) const STATUS_NAMES: [(&str, u16); 62] = [
+    ("CONTINUE", 100),
+    ("SWITCHING_PROTOCOLS", 101),
+    ("PROCESSING", 102),
+    ("EARLY_HINTS", 103),
+    ("OK", 200),
+    ("CREATED", 201),
+    ("ACCEPTED", 202),
+    ("NON_AUTHORITATIVE_INFORMATION", 203),
+    ("NO_CONTENT", 204),
+    ("RESET_CONTENT", 205),
+    ("PARTIAL_CONTENT", 206),
+    ("MULTI_STATUS", 207),
+    ("ALREADY_REPORTED", 208),
+    ("IM_USED", 226),
+    ("MULTIPLE_CHOICES", 300),
+    ("MOVED_PERMANENTLY", 301),
+    ("FOUND", 302),
+    ("SEE_OTHER", 303),
+    ("NOT_MODIFIED", 304),
+    ("USE_PROXY", 305),
+    ("TEMPORARY_REDIRECT", 307),
+    ("PERMANENT_REDIRECT", 308),
+    ("BAD_REQUEST", 400),
+    ("UNAUTHORIZED", 401),
+    ("PAYMENT_REQUIRED", 402),
+    ("FORBIDDEN", 403),
+    ("NOT_FOUND", 404),
+    ("METHOD_NOT_ALLOWED", 405),
+    ("NOT_ACCEPTABLE", 406),
+    ("PROXY_AUTHENTICATION_REQUIRED", 407),
+    ("REQUEST_TIMEOUT", 408),
+    ("CONFLICT", 409),
+    ("GONE", 410),
+    ("LENGTH_REQUIRED", 411),
+    ("PRECONDITION_FAILED", 412),
+    ("PAYLOAD_TOO_LARGE", 413),
+    ("URI_TOO_LONG", 414),
+    ("UNSUPPORTED_MEDIA_TYPE", 415),
+    ("RANGE_NOT_SATISFIABLE", 416),
+    ("EXPECTATION_FAILED", 417),
+    ("IM_A_TEAPOT", 418),
+    ("MISDIRECTED_REQUEST", 421),
+    ("UNPROCESSABLE_ENTITY", 422),
+    ("LOCKED", 423),
+    ("FAILED_DEPENDENCY", 424),
+    ("TOO_EARLY", 425),
+    ("UPGRADE_REQUIRED", 426),
+    ("PRECONDITION_REQUIRED", 428),
+    ("TOO_MANY_REQUESTS", 429),
+    ("REQUEST_HEADER_FIELDS_TOO_LARGE", 431),
+    ("UNAVAILABLE_FOR_LEGAL_REASONS", 451),
+    ("INTERNAL_SERVER_ERROR", 500),
+    ("NOT_IMPLEMENTED", 501),
+    ("BAD_GATEWAY", 502),
+    ("SERVICE_UNAVAILABLE", 503),
+    ("GATEWAY_TIMEOUT", 504),
+    ("HTTP_VERSION_NOT_SUPPORTED", 505),
+    ("VARIANT_ALSO_NEGOTIATES", 506),
+    ("INSUFFICIENT_STORAGE", 507),
+    ("LOOP_DETECTED", 508),
+    ("NOT_EXTENDED", 510),
+    ("NETWORK_AUTHENTICATION_REQUIRED", 511),
+];
+
+/// Any status code can be used, `SUPPORT_STATUS` only have shorthand
+/// constructors.
+pub(crate) const VALID_STATUS: RangeInclusive<u16> = 100..=599;
+
+/// `1XX` to `5XX`
+pub(crate) const SUPPORT_STATUS_RANGE: [u16; 5] = [1, 2, 3, 4, 5];
+
+/// The only well-known status code that is one digit away from `status`, e.g.
+/// `422` for `4222`. There is none for `600`, as `100` to `500` are all as close.
+pub(crate) fn similar_status(status: u16) -> Option<u16> {
+    let status = status.to_string();
+    let mut similar = SUPPORT_STATUS
         .iter()
-        .map(|s| (format_ident!("T{}", s), quote!(#s)))
-        .unzip();
+        .copied()
+        .filter(|support| edit_distance(&support.to_string(), &status) == 1);
+    match (similar.next(), similar.next()) {
+        (Some(similar), None) => Some(similar),
+        _ => None,
+    }
+}
 
-    let expand = quote! {
-        pub enum UniResponse<
-            #(
-                #generics,
-            )*
-        > {
-            #(
-                #generics(#generics),
-            )*
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.as_bytes();
+    let mut prev = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.bytes().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let replace = prev[j] + usize::from(ca != *cb);
+            cur[j + 1] = replace.min(prev[j + 1] + 1).min(cur[j] + 1);
         }
+        prev = cur;
+    }
+    prev[b.len()]
+}
 
-        impl<#(#generics,)*> ::poem_openapi::ApiResponse
-            for UniResponse<#(#generics,)*>
-        where
-            #(
-                #generics: ::poem_openapi::ApiResponse + 'static,
-            )*
-        {
-            const BAD_REQUEST_HANDLER: bool = false;
+/// The status code of the `poem::http::StatusCode` constant `name`, e.g. `404`
+/// for `NOT_FOUND`.
+pub(crate) fn named_status(name: &str) -> Result<u16, String> {
+    if let Some((_, status)) = STATUS_NAMES.iter().find(|(n, _)| *n == name) {
+        return Ok(*status);
+    }
 
-            fn meta() -> ::poem_openapi::registry::MetaResponses {
-                let mut responses = ::std::vec![];
+    let mut message = format!("unknown status code name `{name}`");
+    let similar = STATUS_NAMES
+        .iter()
+        .map(|(n, _)| (edit_distance(n, name), *n))
+        .filter(|(distance, _)| *distance <= 2)
+        .min();
+    if let Some((_, similar)) = similar {
+        message.push_str(&format!(", did you mean `{similar}`?"));
+    } else {
+        message.push_str(", expected a constant of `poem::http::StatusCode` such as `NOT_FOUND`");
+    }
+    Err(message)
+}
 
-                #(
-                    meta_helper::<#generics>(&mut responses, #status);
-                )*
+/// The `SlotId` of every status code, status code range and `default`, and
+/// whether two digits are the same.
+pub(crate) fn generate_slot_ids() -> TokenStream {
+    let digit = |digit: Option<u16>| match digit {
+        Some(digit) => format_ident!("D{}", digit),
+        None => format_ident!("DX"),
+    };
+    let digits = (0..10)
+        .map(Some)
+        .chain([None])
+        .map(digit)
+        .collect::<Vec<_>>();
 
-                ::poem_openapi::registry::MetaResponses { responses }
+    let same = digits.iter().flat_map(|a| {
+        digits.iter().map(move |b| {
+            let output = if a == b { quote!(True) } else { quote!(False) };
+            quote! {
+                impl Same<#b> for #a {
+                    type Output = #output;
+                }
             }
+        })
+    });
 
-            fn register(registry: &mut ::poem_openapi::registry::Registry) {
-                #(
-                    #generics::register(registry);
-                )*
+    let status_ids = VALID_STATUS.map(|status| {
+        let (d0, d1, d2) = (
+            digit(Some(status / 100)),
+            digit(Some(status / 10 % 10)),
+            digit(Some(status % 10)),
+        );
+        quote! {
+            impl SlotId for crate::Status<#status> {
+                type Id = (#d0, #d1, #d2);
             }
         }
+    });
 
-        #[track_caller]
-        fn meta_helper<T: ::poem_openapi::ApiResponse + 'static>(
-            outer_responses: &mut ::std::vec::Vec<::poem_openapi::registry::MetaResponse>,
-            outer_status: u16,
-        ) {
-            let mut inner_responses = T::meta().responses;
-
-            // default empty response
-            if ::std::any::TypeId::of::<T>() == ::std::any::TypeId::of::<crate::Empty>() {
-                return;
+    let status_range_ids = SUPPORT_STATUS_RANGE.iter().map(|class| {
+        let d0 = digit(Some(*class));
+        quote! {
+            impl SlotId for crate::StatusRange<#class> {
+                type Id = (#d0, DX, DX);
             }
+        }
+    });
+
+    quote! {
+        #(
+            pub struct #digits;
+        )*
+
+        #(#same)*
+
+        #(#status_ids)*
+
+        #(#status_range_ids)*
+
+        impl SlotId for crate::DefaultStatus {
+            type Id = (DX, DX, DX);
+        }
+    }
+}
+
+pub(crate) fn generate() -> TokenStream {
+    let status_constructors = SUPPORT_STATUS.iter().map(|status| {
+        let ident = format_ident!("T{}", status);
 
-            if inner_responses.is_empty() {
-                panic!("{} meta responses is empty", ::std::any::type_name::<T>());
+        quote! {
+            pub fn #ident<I>(value: <Self as Select<crate::Status<#status>, I>>::Value) -> Self
+            where
+                Self: Select<crate::Status<#status>, I>,
+            {
+                Self::select(value)
             }
+        }
+    });
 
-            // only get first meta response in T meta responses
-            if let Some(inner_status) = inner_responses.first().unwrap().status {
-                if outer_status == inner_status {
-                    outer_responses.push(inner_responses.swap_remove(0));
-                } else {
-                    panic!(
-                        "status code of the first meta response in {} meta responses is not {}",
-                        ::std::any::type_name::<T>(),
-                        outer_status
-                    );
-                }
-            } else {
-                panic!(
-                    "status code of the first meta response in {} meta responses is none",
-                    ::std::any::type_name::<T>()
-                );
+    let status_range_constructors = SUPPORT_STATUS_RANGE.iter().map(|class| {
+        let ident = format_ident!("T{}XX", class);
+
+        quote! {
+            pub fn #ident<V, I>(
+                status: ::poem::http::StatusCode,
+                value: V,
+            ) -> ::std::result::Result<Self, crate::SlotStatusError>
+            where
+                V: crate::IntoSlotResponse,
+                Self: Select<crate::StatusRange<#class>, I, Value = (::poem::http::StatusCode, V)>,
+            {
+                check_slot_status::<crate::StatusRange<#class>, V>(status, &value)?;
+                ::std::result::Result::Ok(Self::select((status, value)))
             }
         }
+    });
 
-        impl<#(#generics,)*> ::poem::web::IntoResponse
-            for UniResponse<#(#generics,)*>
-        where
+    let expand = quote! {
+        #[allow(non_snake_case)]
+        impl<K: crate::SlotKey, T: Send, N> UniResponse<K, T, N> {
             #(
-                #generics: ::poem::web::IntoResponse,
+                #status_constructors
             )*
-        {
-            fn into_response(self) -> ::poem::Response {
-                match self {
-                    #(
-                        UniResponse::#generics(t) => t.into_response(),
-                    )*
-                }
+
+            #(
+                #status_range_constructors
+            )*
+
+            pub fn Default<V, I>(
+                status: ::poem::http::StatusCode,
+                value: V,
+            ) -> ::std::result::Result<Self, crate::SlotStatusError>
+            where
+                V: crate::IntoSlotResponse,
+                Self: Select<crate::DefaultStatus, I, Value = (::poem::http::StatusCode, V)>,
+            {
+                check_slot_status::<crate::DefaultStatus, V>(status, &value)?;
+                ::std::result::Result::Ok(Self::select((status, value)))
             }
         }
     };
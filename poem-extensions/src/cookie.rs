@@ -0,0 +1,22 @@
+use poem::http::HeaderValue;
+
+/// The `Set-Cookie` value of a cookie declared with
+/// `#[oai(cookie = "session")]`, `attributes` is `"; HttpOnly; Path=/"` etc.
+///
+/// Bytes that are not allowed in a cookie value are percent-encoded.
+#[doc(hidden)]
+pub fn set_cookie(name: &str, value: &HeaderValue, attributes: &str) -> HeaderValue {
+    let mut cookie = String::with_capacity(name.len() + value.len() + attributes.len() + 1);
+    cookie.push_str(name);
+    cookie.push('=');
+    for &b in value.as_bytes() {
+        // cookie-octet of RFC 6265 except `%`, so that the value can be decoded
+        if matches!(b, 0x21 | 0x23 | 0x24 | 0x26..=0x2B | 0x2D..=0x3A | 0x3C..=0x5B | 0x5D..=0x7E) {
+            cookie.push(b as char);
+        } else {
+            cookie.push_str(&format!("%{b:02X}"));
+        }
+    }
+    cookie.push_str(attributes);
+    HeaderValue::try_from(cookie).expect("checked by `#[derive(OneResponse)]`")
+}
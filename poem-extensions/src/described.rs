@@ -0,0 +1,83 @@
+use std::marker::PhantomData;
+
+use poem::http::StatusCode;
+use poem_openapi::registry::{MetaResponse, MetaSchema, MetaSchemaRef};
+use serde_json::Value;
+
+use crate::{SlotKey, SlotStatusError};
+
+/// The slot key `K` with the description and the example of `D`, written in
+/// a named `response!` as `404: Json<ErrorBody> => "user not found"`,
+/// optionally followed by `, example = r#"{"message": "user not found"}"#`.
+pub struct Described<K, D>(PhantomData<(K, D)>);
+
+/// The description and the example of a slot, implemented by the marker
+/// types generated by `response! { type Name = { .. }; }`.
+#[doc(hidden)]
+pub trait Description {
+    const DESCRIPTION: &'static str;
+    /// A JSON value, only documented for the JSON media types.
+    const EXAMPLE: Option<&'static str>;
+}
+
+impl<K: SlotKey, D: Description> SlotKey for Described<K, D> {
+    const STATUS: Option<u16> = K::STATUS;
+    const STATUS_RANGE: Option<&'static str> = K::STATUS_RANGE;
+    const BAD_REQUEST: bool = K::BAD_REQUEST;
+
+    type Key = K::Key;
+
+    type Value<T>
+        = K::Value<T>
+    where
+        T: Send;
+
+    fn into_parts<T: Send>(value: Self::Value<T>) -> (StatusCode, T) {
+        K::into_parts(value)
+    }
+
+    fn check(status: StatusCode) -> Result<(), SlotStatusError> {
+        K::check(status)
+    }
+
+    fn from_value<T: Send>(value: T) -> Self::Value<T> {
+        K::from_value(value)
+    }
+
+    fn describe(response: &mut MetaResponse) {
+        K::describe(response);
+
+        response.description = D::DESCRIPTION;
+
+        if let Some(example) = D::EXAMPLE {
+            let example: Value =
+                serde_json::from_str(example).expect("the example is checked by `response!`");
+            for media_type in &mut response.content {
+                if is_json(media_type.content_type) {
+                    set_example(&mut media_type.schema, example.clone());
+                }
+            }
+        }
+    }
+}
+
+/// `application/json` or a `+json` type such as `application/problem+json`
+fn is_json(content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    essence.eq_ignore_ascii_case("application/json")
+        || essence.to_ascii_lowercase().ends_with("+json")
+}
+
+fn set_example(schema: &mut MetaSchemaRef, example: Value) {
+    match schema {
+        MetaSchemaRef::Inline(schema) => schema.example = Some(example),
+        MetaSchemaRef::Reference(_) => {
+            let reference = std::mem::replace(schema, MetaSchemaRef::Reference(String::new()));
+            *schema = MetaSchemaRef::Inline(Box::new(MetaSchema {
+                all_of: vec![reference],
+                example: Some(example),
+                ..MetaSchema::ANY
+            }));
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use poem::{http::StatusCode, IntoResponse, Response};
+use poem_openapi::{
+    registry::{MetaMediaType, MetaResponse, MetaResponses, Registry},
+    ApiResponse, ResponseContent,
+};
+
+use crate::{IntoSlotResponse, Slot};
+
+/// One of several payload types of the same status code, written as
+/// `200: Json<T> | Xml<T>` in `response!`.
+///
+/// Every media type is documented, and the chosen payload sets its own
+/// `Content-Type`. More than two payload types are nested, `A | B | C` is
+/// `Either<A, Either<B, C>>`.
+pub enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+impl<A: ResponseContent, B: ResponseContent> ResponseContent for Either<A, B> {
+    fn media_types() -> Vec<MetaMediaType> {
+        let mut media_types = A::media_types();
+        media_types.extend(B::media_types());
+        media_types
+    }
+
+    fn register(registry: &mut Registry) {
+        A::register(registry);
+        B::register(registry);
+    }
+}
+
+impl<A, B> ApiResponse for Either<A, B>
+where
+    A: ResponseContent + IntoResponse,
+    B: ResponseContent + IntoResponse,
+{
+    fn meta() -> MetaResponses {
+        MetaResponses {
+            responses: vec![MetaResponse {
+                description: "",
+                status: Some(200),
+                status_range: None,
+                content: <Self as ResponseContent>::media_types(),
+                headers: vec![],
+            }],
+        }
+    }
+
+    fn register(registry: &mut Registry) {
+        <Self as ResponseContent>::register(registry);
+    }
+}
+
+impl<A: IntoResponse, B: IntoResponse> IntoResponse for Either<A, B> {
+    fn into_response(self) -> Response {
+        match self {
+            Either::Left(a) => a.into_response(),
+            Either::Right(b) => b.into_response(),
+        }
+    }
+}
+
+impl<A: Slot<K>, B: Slot<K>, K> Slot<K> for Either<A, B> {}
+
+impl<A: IntoSlotResponse, B: IntoSlotResponse> IntoSlotResponse for Either<A, B> {
+    fn into_slot_response(self, status: StatusCode) -> Response {
+        match self {
+            Either::Left(a) => a.into_slot_response(status),
+            Either::Right(b) => b.into_slot_response(status),
+        }
+    }
+
+    fn status(&self) -> Option<StatusCode> {
+        match self {
+            Either::Left(a) => a.status(),
+            Either::Right(b) => b.status(),
+        }
+    }
+
+    fn into_slot_error(self, status: StatusCode) -> poem::Error {
+        match self {
+            Either::Left(a) => a.into_slot_error(status),
+            Either::Right(b) => b.into_slot_error(status),
+        }
+    }
+}
@@ -1,14 +1,16 @@
-use poem::{Body, IntoResponse, Response};
+use poem::{IntoResponse, Response};
 use poem_openapi::{
     registry::{MetaResponses, Registry},
     ApiResponse,
 };
 
-pub struct Empty;
+/// The end of a [`UniResponse`](crate::UniResponse), it has no value so that
+/// no response is ever sent from it.
+pub enum Empty {}
 
 impl IntoResponse for Empty {
     fn into_response(self) -> Response {
-        Response::builder().body(Body::empty())
+        match self {}
     }
 }
 
@@ -21,3 +23,9 @@ impl ApiResponse for Empty {
 
     fn register(_registry: &mut Registry) {}
 }
+
+impl From<Empty> for poem::Error {
+    fn from(resp: Empty) -> Self {
+        match resp {}
+    }
+}
@@ -0,0 +1,191 @@
+use std::marker::PhantomData;
+
+use poem::{
+    http::{header::HeaderName, HeaderMap},
+    IntoResponse, Response,
+};
+use poem_openapi::{
+    registry::{MetaHeader, MetaResponses, Registry},
+    types::{ToHeader, Type},
+    ApiResponse,
+};
+
+use crate::ErrorMetadata;
+
+/// The header declared by `S` with a value of type `T`, written in a named
+/// `response!` as `"X-Request-Id": String => "request id"`.
+pub struct Header<S, T>(PhantomData<(S, T)>);
+
+/// The name and the description of a header, implemented by the marker types
+/// generated by `response! { type Name = { headers { .. }, .. }; }`.
+#[doc(hidden)]
+pub trait HeaderSpec {
+    const NAME: &'static str;
+    const DESCRIPTION: Option<&'static str>;
+}
+
+/// A header declared in the `headers { .. }` block of `response!`.
+pub trait HeaderDef {
+    type Value: ToHeader + Type;
+
+    fn meta() -> MetaHeader;
+
+    fn insert(value: Self::Value, headers: &mut HeaderMap);
+}
+
+impl<S: HeaderSpec, T: ToHeader + Type> HeaderDef for Header<S, T> {
+    type Value = T;
+
+    fn meta() -> MetaHeader {
+        MetaHeader {
+            name: S::NAME.to_string(),
+            description: S::DESCRIPTION.map(ToString::to_string),
+            required: T::IS_REQUIRED,
+            deprecated: false,
+            schema: T::schema_ref(),
+        }
+    }
+
+    fn insert(value: T, headers: &mut HeaderMap) {
+        if let Some(value) = value.to_header() {
+            let name = HeaderName::try_from(S::NAME).expect("checked by `response!`");
+            headers.insert(name, value);
+        }
+    }
+}
+
+/// The headers declared in the `headers { .. }` block of `response!`, a tuple
+/// of up to 8 [`Header`].
+#[diagnostic::on_unimplemented(
+    message = "the headers of `WithHeaders` must be a tuple of up to 8 `Header`",
+    label = "too many headers, or not a tuple of `Header`",
+    note = "the `headers {{ .. }}` block of `response!` declares at most 8 headers"
+)]
+pub trait Headers {
+    /// The values of the headers, in declaration order.
+    type Values: Send;
+
+    fn meta() -> Vec<MetaHeader>;
+
+    fn register(registry: &mut Registry);
+
+    fn insert(values: Self::Values, headers: &mut HeaderMap);
+}
+
+macro_rules! impl_headers_for_tuples {
+    ($(($($h:ident $v:ident),+)),* $(,)?) => {
+        $(
+            impl<$($h: HeaderDef),+> Headers for ($($h,)+)
+            where
+                $($h::Value: Send,)+
+            {
+                type Values = ($($h::Value,)+);
+
+                fn meta() -> Vec<MetaHeader> {
+                    vec![$($h::meta()),+]
+                }
+
+                fn register(registry: &mut Registry) {
+                    $(<$h::Value as Type>::register(registry);)+
+                }
+
+                fn insert(($($v,)+): Self::Values, headers: &mut HeaderMap) {
+                    $($h::insert($v, headers);)+
+                }
+            }
+        )*
+    };
+}
+
+impl_headers_for_tuples!(
+    (H1 v1),
+    (H1 v1, H2 v2),
+    (H1 v1, H2 v2, H3 v3),
+    (H1 v1, H2 v2, H3 v3, H4 v4),
+    (H1 v1, H2 v2, H3 v3, H4 v4, H5 v5),
+    (H1 v1, H2 v2, H3 v3, H4 v4, H5 v5, H6 v6),
+    (H1 v1, H2 v2, H3 v3, H4 v4, H5 v5, H6 v6, H7 v7),
+    (H1 v1, H2 v2, H3 v3, H4 v4, H5 v5, H6 v6, H7 v7, H8 v8),
+);
+
+/// A response `R` with the headers `H` that are documented for every status
+/// code of `R`, written in `response!` as
+/// `headers { "X-Request-Id": String }, 200: .., 404: ..`.
+///
+/// The values of the headers are given once by [`WithHeaders::new`], a
+/// response made by a bad request handler has no values. At most 8 headers
+/// can be declared, [`Headers`] is implemented for tuples of up to 8.
+pub struct WithHeaders<H: Headers, R> {
+    response: R,
+    headers: Option<H::Values>,
+    _headers: PhantomData<fn() -> H>,
+}
+
+impl<H: Headers, R> WithHeaders<H, R> {
+    pub fn new(response: R, headers: H::Values) -> Self {
+        Self {
+            response,
+            headers: Some(headers),
+            _headers: PhantomData,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.response
+    }
+}
+
+impl<H: Headers, R: ApiResponse> ApiResponse for WithHeaders<H, R> {
+    const BAD_REQUEST_HANDLER: bool = R::BAD_REQUEST_HANDLER;
+
+    fn meta() -> MetaResponses {
+        let mut meta = R::meta();
+        for response in &mut meta.responses {
+            response.headers.extend(H::meta());
+        }
+        meta
+    }
+
+    fn register(registry: &mut Registry) {
+        R::register(registry);
+        H::register(registry);
+    }
+
+    fn from_parse_request_error(err: poem::Error) -> Self {
+        Self {
+            response: R::from_parse_request_error(err),
+            headers: None,
+            _headers: PhantomData,
+        }
+    }
+}
+
+impl<H: Headers, R: IntoResponse> IntoResponse for WithHeaders<H, R> {
+    fn into_response(self) -> Response {
+        let mut resp = self.response.into_response();
+        if let Some(values) = self.headers {
+            H::insert(values, resp.headers_mut());
+        }
+        resp
+    }
+}
+
+impl<H: Headers, R: Into<poem::Error>> From<WithHeaders<H, R>> for poem::Error {
+    fn from(resp: WithHeaders<H, R>) -> Self {
+        let err: poem::Error = resp.response.into();
+        let Some(values) = resp.headers else {
+            return err;
+        };
+
+        let message = err.to_string();
+        let metadata = err.data::<ErrorMetadata>().cloned();
+        let mut resp = err.into_response();
+        H::insert(values, resp.headers_mut());
+        let mut err = poem::Error::from_response(resp);
+        err.set_error_message(message);
+        if let Some(metadata) = metadata {
+            err.set_data(metadata);
+        }
+        err
+    }
+}
@@ -1,6 +1,31 @@
+mod cookie;
+mod described;
+mod either;
 mod empty;
+mod headers;
+mod negotiate;
+mod one_response;
+mod problem;
+mod response_set;
+mod slot;
+mod slot_id;
+mod status;
 mod uni_response;
 
+pub use cookie::set_cookie;
+pub use described::{Described, Description};
+pub use either::Either;
 pub use empty::Empty;
-pub use poem_extensions_macro::{api, response, OneResponse, UniOpenApi};
-pub use uni_response::UniResponse;
+pub use headers::{Header, HeaderDef, HeaderSpec, Headers, WithHeaders};
+pub use negotiate::{Negotiate, NotAcceptable, Representation};
+pub use one_response::{merge_media_types, ErrorMetadata, OneResponse, SameResponse};
+pub use poem_extensions_macro::{
+    api, response, ErrorResponse, OneResponse, ProblemResponse, UniOpenApi,
+};
+pub use problem::{NoExtensions, Problem, ProblemExtensions};
+pub use response_set::ResponseSet;
+pub use slot::{IntoSlotResponse, KeepStatus, Slot};
+pub use status::{DefaultStatus, SlotKey, SlotStatusError, Status, StatusRange};
+pub use uni_response::{
+    Here, Insert, InsertAt, OtherStatus, Select, There, UniResponse, Widen, WidenResult, Widened,
+};
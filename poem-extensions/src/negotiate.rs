@@ -0,0 +1,156 @@
+use poem::{http::StatusCode, web::Accept, IntoResponse, Response};
+use poem_openapi::{
+    payload::{Base64, Binary, Html, Json, Payload, PlainText, Xml, Yaml},
+    registry::{MetaResponse, MetaResponses, Registry},
+    ApiResponse,
+};
+
+use crate::{slot::with_status, Either, IntoSlotResponse, OneResponse, Slot, Status};
+
+/// A payload type that renders a value of type `T`.
+///
+/// It is implemented for the payload types of `poem_openapi` that wrap the
+/// value, e.g. `Json<T>` for `T`. Implement it to render a value with another
+/// payload type, e.g. `PlainText<String>` for `User`.
+pub trait Representation<T>: Payload {
+    fn represent(value: T) -> Self;
+}
+
+macro_rules! impl_representation_for_payloads {
+    ($($ty:ident),* $(,)?) => {
+        $(
+            impl<T> Representation<T> for $ty<T>
+            where
+                $ty<T>: Payload,
+            {
+                fn represent(value: T) -> Self {
+                    $ty(value)
+                }
+            }
+        )*
+    };
+}
+
+impl_representation_for_payloads!(Base64, Binary, Html, Json, PlainText, Xml, Yaml);
+
+/// Picks the payload type to render a value of type `T` from the `Accept`
+/// header of the request.
+///
+/// It is implemented for every [`Representation`] and for [`Either`] of them,
+/// so `Either<Json<T>, Yaml<T>>` renders `T` as JSON or YAML, preferring JSON.
+pub trait Negotiate<T>: Sized {
+    /// Renders `value` with the first payload type whose content type is
+    /// accepted by `accepts`, or gives it back if none is.
+    fn select(accepts: &dyn Fn(&str) -> bool, value: T) -> Result<Self, T>;
+
+    /// Renders `value` with the payload type of the most preferred media type
+    /// in `accept`. An empty `Accept` header accepts any media type, and a
+    /// media type with `q=0` is never chosen.
+    fn negotiate(accept: &Accept, value: T) -> Result<Self, NotAcceptable> {
+        if accept.0.is_empty() {
+            return Self::select(&|_| true, value).map_err(|_| NotAcceptable);
+        }
+
+        let mut value = value;
+        for mime in &accept.0 {
+            let accepts = |content_type: &str| {
+                matches(mime.type_().as_str(), mime.subtype().as_str(), content_type)
+                    && !refused(accept, content_type)
+            };
+
+            match Self::select(&accepts, value) {
+                Ok(payload) => return Ok(payload),
+                Err(v) => value = v,
+            }
+        }
+        Err(NotAcceptable)
+    }
+}
+
+impl<T, P: Representation<T>> Negotiate<T> for P {
+    fn select(accepts: &dyn Fn(&str) -> bool, value: T) -> Result<Self, T> {
+        if accepts(P::CONTENT_TYPE) {
+            Ok(P::represent(value))
+        } else {
+            Err(value)
+        }
+    }
+}
+
+impl<T, A: Negotiate<T>, B: Negotiate<T>> Negotiate<T> for Either<A, B> {
+    fn select(accepts: &dyn Fn(&str) -> bool, value: T) -> Result<Self, T> {
+        A::select(accepts, value)
+            .map(Either::Left)
+            .or_else(|value| B::select(accepts, value).map(Either::Right))
+    }
+}
+
+/// Whether `content_type` falls in the media range `range_type/range_subtype`.
+fn matches(range_type: &str, range_subtype: &str, content_type: &str) -> bool {
+    let essence = content_type.split(';').next().unwrap_or_default().trim();
+    let Some((type_, subtype)) = essence.split_once('/') else {
+        return false;
+    };
+
+    (range_type == "*" || range_type.eq_ignore_ascii_case(type_))
+        && (range_subtype == "*" || range_subtype.eq_ignore_ascii_case(subtype))
+}
+
+/// Whether the most specific media range in `accept` that matches
+/// `content_type` has `q=0`.
+fn refused(accept: &Accept, content_type: &str) -> bool {
+    accept
+        .0
+        .iter()
+        .filter(|mime| matches(mime.type_().as_str(), mime.subtype().as_str(), content_type))
+        .max_by_key(|mime| (mime.type_() != "*", mime.subtype() != "*"))
+        .and_then(|mime| mime.get_param("q"))
+        .is_some_and(|q| q.as_str().parse::<f32>() == Ok(0.0))
+}
+
+/// The `406 Not Acceptable` response, returned when none of the payload types
+/// matches the `Accept` header.
+#[derive(Debug)]
+pub struct NotAcceptable;
+
+impl IntoResponse for NotAcceptable {
+    fn into_response(self) -> Response {
+        StatusCode::NOT_ACCEPTABLE.into_response()
+    }
+}
+
+impl ApiResponse for NotAcceptable {
+    fn meta() -> MetaResponses {
+        MetaResponses {
+            responses: vec![MetaResponse {
+                description: "Not acceptable",
+                status: Some(406),
+                status_range: None,
+                content: vec![],
+                headers: vec![],
+            }],
+        }
+    }
+
+    fn register(_registry: &mut Registry) {}
+}
+
+impl OneResponse for NotAcceptable {
+    const STATUS: u16 = 406;
+
+    type Key = Status<406>;
+}
+
+impl Slot<Status<406>> for NotAcceptable {}
+
+impl IntoSlotResponse for NotAcceptable {
+    fn into_slot_response(self, status: StatusCode) -> Response {
+        with_status(self, status)
+    }
+}
+
+impl From<NotAcceptable> for poem::Error {
+    fn from(_: NotAcceptable) -> Self {
+        poem::Error::from_status(StatusCode::NOT_ACCEPTABLE)
+    }
+}
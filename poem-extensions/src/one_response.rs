@@ -0,0 +1,97 @@
+use poem::{http::StatusCode, IntoResponse, Response};
+use poem_openapi::{
+    registry::{MetaMediaType, MetaSchema, MetaSchemaRef},
+    ApiResponse,
+};
+
+use crate::SlotKey;
+
+/// A response that is documented with exactly one status code, implemented by
+/// `#[derive(OneResponse)]`.
+pub trait OneResponse: ApiResponse + IntoResponse {
+    /// The status code declared by `#[oai(status = ...)]`.
+    const STATUS: u16;
+
+    /// The slot key of the status code, `Status<STATUS>`.
+    type Key: SlotKey;
+}
+
+/// `Self` is the response type `T`, asserted by `#[derive(ErrorResponse)]`
+/// for the variants mapped to one status code.
+#[doc(hidden)]
+#[diagnostic::on_unimplemented(
+    message = "the variants mapped to one status code must have the same response type",
+    label = "expected `{T}`, found `{Self}`"
+)]
+pub trait SameResponse<T> {}
+
+impl<T> SameResponse<T> for T {}
+
+/// Merges the media types of the variants of a `#[derive(OneResponse)]` enum,
+/// the different schemas of one content type become a `oneOf`.
+#[doc(hidden)]
+pub fn merge_media_types(content: Vec<MetaMediaType>) -> Vec<MetaMediaType> {
+    let mut merged: Vec<(&'static str, Vec<MetaSchemaRef>)> = Vec::new();
+    for media_type in content {
+        match merged
+            .iter_mut()
+            .find(|(content_type, _)| *content_type == media_type.content_type)
+        {
+            Some((_, schemas)) => {
+                if !schemas.contains(&media_type.schema) {
+                    schemas.push(media_type.schema);
+                }
+            }
+            None => merged.push((media_type.content_type, vec![media_type.schema])),
+        }
+    }
+
+    merged
+        .into_iter()
+        .map(|(content_type, mut schemas)| MetaMediaType {
+            content_type,
+            schema: if schemas.len() == 1 {
+                schemas.remove(0)
+            } else {
+                MetaSchemaRef::Inline(Box::new(MetaSchema {
+                    one_of: schemas,
+                    ..MetaSchema::ANY
+                }))
+            },
+        })
+        .collect()
+}
+
+/// The metadata of a `poem::Error` made from a
+/// [`OneResponse`](trait@OneResponse), read with
+/// `err.data::<ErrorMetadata>()`, e.g. by an error-logging middleware.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorMetadata {
+    /// The type name of the response.
+    pub type_name: &'static str,
+    /// The variant of an enum response.
+    pub variant: Option<&'static str>,
+    pub status: StatusCode,
+    pub content_type: Option<String>,
+    /// A summary of the body without its values, the type name of the payload
+    /// or the text returned by `#[oai(error_summary_with = "...")]`.
+    pub body: Option<String>,
+}
+
+impl ErrorMetadata {
+    #[doc(hidden)]
+    pub fn new(
+        type_name: &'static str,
+        variant: Option<&'static str>,
+        body: Option<String>,
+        resp: &Response,
+    ) -> Self {
+        Self {
+            type_name,
+            variant,
+            status: resp.status(),
+            content_type: resp.content_type().map(ToString::to_string),
+            body,
+        }
+    }
+}
@@ -0,0 +1,215 @@
+use poem::{http::StatusCode, IntoResponse, Response};
+use poem_openapi::{
+    payload::Payload,
+    registry::{MetaMediaType, MetaResponse, MetaResponses, MetaSchema, MetaSchemaRef, Registry},
+    types::{ToJSON, Type},
+    ApiResponse,
+};
+use serde_json::{Map, Value};
+
+use crate::{slot::with_status, IntoSlotResponse, Slot};
+
+/// The problem details of RFC 9457, sent as `application/problem+json`.
+///
+/// The members of `E`, a type that is serialized as a JSON object, are sent
+/// next to the standard members as extension members. The `status` member is
+/// set to the status code the problem is sent with, by the slot of a
+/// [`UniResponse`](crate::UniResponse) or by `#[oai(problem)]`. In a status
+/// code range slot that is the status code given to `T4XX` etc., or the
+/// `#[oai(status)]` field of a `ProblemResponse` with `status_range`.
+pub struct Problem<E = NoExtensions> {
+    /// A URI reference that identifies the problem type, `about:blank` when
+    /// absent.
+    pub ty: Option<String>,
+    pub title: Option<String>,
+    pub status: Option<u16>,
+    pub detail: Option<String>,
+    pub instance: Option<String>,
+    pub extensions: E,
+}
+
+impl Problem {
+    pub fn new() -> Self {
+        Self::with_extensions(NoExtensions)
+    }
+}
+
+impl Default for Problem {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Problem<E> {
+    pub fn with_extensions(extensions: E) -> Self {
+        Self {
+            ty: None,
+            title: None,
+            status: None,
+            detail: None,
+            instance: None,
+            extensions,
+        }
+    }
+
+    pub fn ty(mut self, ty: impl Into<String>) -> Self {
+        self.ty = Some(ty.into());
+        self
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn status(mut self, status: StatusCode) -> Self {
+        self.status = Some(status.as_u16());
+        self
+    }
+
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+
+    pub fn instance(mut self, instance: impl Into<String>) -> Self {
+        self.instance = Some(instance.into());
+        self
+    }
+}
+
+/// A [`Problem`] without extension members.
+pub struct NoExtensions;
+
+/// The extension members of a [`Problem`], implemented for [`NoExtensions`]
+/// and for every type that is serialized as a JSON object, e.g. an `Object`.
+pub trait ProblemExtensions: Send {
+    fn schema_ref() -> Option<MetaSchemaRef>;
+
+    fn register(registry: &mut Registry);
+
+    fn members(&self) -> Map<String, Value>;
+}
+
+impl ProblemExtensions for NoExtensions {
+    fn schema_ref() -> Option<MetaSchemaRef> {
+        None
+    }
+
+    fn register(_registry: &mut Registry) {}
+
+    fn members(&self) -> Map<String, Value> {
+        Map::new()
+    }
+}
+
+impl<T: ToJSON> ProblemExtensions for T {
+    fn schema_ref() -> Option<MetaSchemaRef> {
+        Some(T::schema_ref())
+    }
+
+    fn register(registry: &mut Registry) {
+        T::register(registry);
+    }
+
+    fn members(&self) -> Map<String, Value> {
+        match self.to_json() {
+            Some(Value::Object(members)) => members,
+            _ => Map::new(),
+        }
+    }
+}
+
+impl<E: ProblemExtensions> Payload for Problem<E> {
+    const CONTENT_TYPE: &'static str = "application/problem+json";
+
+    fn schema_ref() -> MetaSchemaRef {
+        let problem = MetaSchemaRef::Reference("Problem".to_string());
+        match E::schema_ref() {
+            Some(extensions) => MetaSchemaRef::Inline(Box::new(MetaSchema {
+                all_of: vec![problem, extensions],
+                ..MetaSchema::ANY
+            })),
+            None => problem,
+        }
+    }
+
+    fn register(registry: &mut Registry) {
+        registry.create_schema::<Problem, _>("Problem".to_string(), |_| {
+            let uri = || {
+                MetaSchemaRef::Inline(Box::new(MetaSchema::new_with_format(
+                    "string",
+                    "uri-reference",
+                )))
+            };
+            MetaSchema {
+                description: Some("The problem details of RFC 9457"),
+                properties: vec![
+                    ("type", uri()),
+                    ("title", <String as Type>::schema_ref()),
+                    ("status", <u16 as Type>::schema_ref()),
+                    ("detail", <String as Type>::schema_ref()),
+                    ("instance", uri()),
+                ],
+                ..MetaSchema::new("object")
+            }
+        });
+        E::register(registry);
+    }
+}
+
+impl<E: ProblemExtensions> IntoResponse for Problem<E> {
+    fn into_response(self) -> Response {
+        let mut members = Map::new();
+        let standard = [
+            ("type", self.ty.map(Value::from)),
+            ("title", self.title.map(Value::from)),
+            ("status", self.status.map(Value::from)),
+            ("detail", self.detail.map(Value::from)),
+            ("instance", self.instance.map(Value::from)),
+        ];
+        for (name, value) in standard {
+            if let Some(value) = value {
+                members.insert(name.to_string(), value);
+            }
+        }
+        // an extension member cannot replace a standard member
+        for (name, value) in self.extensions.members() {
+            members.entry(name).or_insert(value);
+        }
+
+        Response::builder()
+            .content_type(Self::CONTENT_TYPE)
+            .body(Value::Object(members).to_string())
+    }
+}
+
+impl<E: ProblemExtensions> ApiResponse for Problem<E> {
+    fn meta() -> MetaResponses {
+        MetaResponses {
+            responses: vec![MetaResponse {
+                description: "",
+                status: Some(200),
+                status_range: None,
+                content: vec![MetaMediaType {
+                    content_type: Self::CONTENT_TYPE,
+                    schema: Self::schema_ref(),
+                }],
+                headers: vec![],
+            }],
+        }
+    }
+
+    fn register(registry: &mut Registry) {
+        <Self as Payload>::register(registry);
+    }
+}
+
+impl<E, K> Slot<K> for Problem<E> {}
+
+impl<E: ProblemExtensions> IntoSlotResponse for Problem<E> {
+    fn into_slot_response(mut self, status: StatusCode) -> Response {
+        self.status = Some(status.as_u16());
+        with_status(self, status)
+    }
+}
@@ -0,0 +1,20 @@
+use crate::{Empty, SlotKey, UniResponse};
+
+/// A list of responses that can be spread into `response!` with `..Set`,
+/// declared as `type CommonErrors = response! { 401: .., 500: .. };`.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` is not a response set",
+    note = "declare a response set with `type CommonErrors = response! {{ ... }};`"
+)]
+pub trait ResponseSet {
+    /// The responses of this set followed by the responses of `B`.
+    type Concat<B: ResponseSet>: ResponseSet;
+}
+
+impl ResponseSet for Empty {
+    type Concat<B: ResponseSet> = B;
+}
+
+impl<K: SlotKey, T: Send, N: ResponseSet> ResponseSet for UniResponse<K, T, N> {
+    type Concat<B: ResponseSet> = UniResponse<K, T, N::Concat<B>>;
+}
@@ -0,0 +1,185 @@
+use std::any::{type_name, TypeId};
+
+use futures_util::Stream;
+use poem::{http::StatusCode, IntoResponse, Response};
+use poem_openapi::{
+    payload::{
+        Attachment, Base64, Binary, EventStream, Html, Json, PlainText,
+        Response as PayloadResponse, Xml, Yaml,
+    },
+    registry::{MetaResponse, MetaResponses, Registry},
+    ApiResponse,
+};
+
+use crate::Empty;
+
+/// Marks the types that can be placed in the `K` slot of a
+/// [`UniResponse`](crate::UniResponse), where `K` is one of [`Status`],
+/// [`StatusRange`] or [`DefaultStatus`].
+///
+/// It is implemented for the payload types of `poem_openapi`, `()`, [`Empty`],
+/// [`KeepStatus`], [`Problem`](crate::Problem) and for the types derived by
+/// [`OneResponse`](crate::OneResponse) with the slot of `#[oai(status = ...)]`
+/// or `#[oai(status_range = ...)]`. A custom payload type can implement it for
+/// every slot, along with [`IntoSlotResponse`]. A type derived by
+/// `poem_openapi::ApiResponse` is placed in a slot as [`KeepStatus<T>`].
+///
+/// [`Status`]: crate::Status
+/// [`StatusRange`]: crate::StatusRange
+/// [`DefaultStatus`]: crate::DefaultStatus
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be placed in the `{K}` slot of `UniResponse`",
+    label = "`{Self}` is not a response of `{K}`",
+    note = "a `OneResponse` can only be placed in the slot declared by `#[oai(status = ...)]` or `#[oai(status_range = ...)]`"
+)]
+pub trait Slot<K> {}
+
+macro_rules! impl_slot_for_payloads {
+    ($($ty:ident),* $(,)?) => {
+        $(
+            impl<T, K> Slot<K> for $ty<T> {}
+
+            impl<T> IntoSlotResponse for $ty<T>
+            where
+                $ty<T>: IntoResponse,
+            {
+                fn into_slot_response(self, status: StatusCode) -> Response {
+                    with_status(self, status)
+                }
+            }
+        )*
+    };
+}
+
+impl_slot_for_payloads!(Attachment, Base64, Binary, Html, Json, PlainText, Xml, Yaml);
+
+impl<T: Stream + Send + 'static, K> Slot<K> for EventStream<T> {}
+
+impl<T: Stream + Send + 'static> IntoSlotResponse for EventStream<T>
+where
+    EventStream<T>: IntoResponse,
+{
+    fn into_slot_response(self, status: StatusCode) -> Response {
+        with_status(self, status)
+    }
+}
+
+impl<T: Slot<K>, K> Slot<K> for PayloadResponse<T> {}
+
+impl<T> IntoSlotResponse for PayloadResponse<T>
+where
+    PayloadResponse<T>: IntoResponse,
+{
+    fn into_slot_response(self, status: StatusCode) -> Response {
+        with_status(self, status)
+    }
+}
+
+impl<K> Slot<K> for () {}
+
+impl IntoSlotResponse for () {
+    fn into_slot_response(self, status: StatusCode) -> Response {
+        with_status(self, status)
+    }
+}
+
+impl<K> Slot<K> for Empty {}
+
+impl IntoSlotResponse for Empty {
+    fn into_slot_response(self, _status: StatusCode) -> Response {
+        match self {}
+    }
+}
+
+impl<T, K> Slot<K> for KeepStatus<T> {}
+
+/// Converts the value of a [`UniResponse`](crate::UniResponse) slot into a
+/// response with the status code of that slot.
+pub trait IntoSlotResponse: Send + Sized {
+    /// If true, the status codes documented by the inner response are kept
+    /// as they are.
+    const KEEP_STATUS: bool = false;
+
+    fn into_slot_response(self, status: StatusCode) -> Response;
+
+    /// The status code the value carries itself, e.g. the `#[oai(status)]`
+    /// field of a type derived by [`OneResponse`](crate::OneResponse) with
+    /// `status_range`. It is sent instead of the status code given to the slot.
+    fn status(&self) -> Option<StatusCode> {
+        None
+    }
+
+    /// Converts into an error with the status code of the slot, the types
+    /// derived by [`OneResponse`](crate::OneResponse) keep their error message.
+    fn into_slot_error(self, status: StatusCode) -> poem::Error {
+        poem::Error::from_response(self.into_slot_response(status))
+    }
+}
+
+/// Sends `resp` with the status code `status`.
+pub(crate) fn with_status(resp: impl IntoResponse, status: StatusCode) -> Response {
+    let mut resp = resp.into_response();
+    resp.set_status(status);
+    resp
+}
+
+/// Keeps the status code chosen by the inner response instead of the status
+/// code of the [`UniResponse`](crate::UniResponse) slot it is placed in.
+pub struct KeepStatus<T>(pub T);
+
+impl<T: IntoResponse + Send> IntoSlotResponse for KeepStatus<T> {
+    const KEEP_STATUS: bool = true;
+
+    fn into_slot_response(self, _status: StatusCode) -> Response {
+        self.0.into_response()
+    }
+}
+
+impl<T: ApiResponse> ApiResponse for KeepStatus<T> {
+    const BAD_REQUEST_HANDLER: bool = T::BAD_REQUEST_HANDLER;
+
+    fn meta() -> MetaResponses {
+        T::meta()
+    }
+
+    fn register(registry: &mut Registry) {
+        T::register(registry);
+    }
+
+    fn from_parse_request_error(err: poem::Error) -> Self {
+        KeepStatus(T::from_parse_request_error(err))
+    }
+}
+
+/// Pushes the meta response of the slot value `T` into `outer_responses`,
+/// documented with the status code of the slot.
+#[track_caller]
+pub(crate) fn meta_helper<T: ApiResponse + IntoSlotResponse + 'static>(
+    outer_responses: &mut Vec<MetaResponse>,
+    outer_status: Option<u16>,
+    outer_status_range: Option<&str>,
+) {
+    let mut inner_responses = T::meta().responses;
+
+    // default empty response
+    if TypeId::of::<T>() == TypeId::of::<Empty>() {
+        return;
+    }
+
+    if inner_responses.is_empty() {
+        panic!("{} meta responses is empty", type_name::<T>());
+    }
+
+    // the inner response picks the status code itself
+    if T::KEEP_STATUS {
+        outer_responses.append(&mut inner_responses);
+        return;
+    }
+
+    // only get first meta response in T meta responses, it is always sent with the
+    // status code of the slot
+    let mut inner_response = inner_responses.swap_remove(0);
+    inner_response.status = outer_status;
+    inner_response.status_range = outer_status_range.map(ToString::to_string);
+    outer_responses.push(inner_response);
+}
@@ -0,0 +1,80 @@
+//! The slot keys of a [`UniResponse`](crate::UniResponse) compared at compile
+//! time, so that a duplicate slot or a response without a slot is a type
+//! error that `cargo check` reports.
+
+use crate::{Empty, SlotKey, UniResponse};
+
+/// The identity of a slot key as three digits, e.g. `(D4, D0, D4)` for
+/// `Status<404>`, `(D4, DX, DX)` for `StatusRange<4>` and `(DX, DX, DX)` for
+/// `DefaultStatus`.
+pub trait SlotId {
+    type Id;
+}
+
+pub struct True;
+
+pub struct False;
+
+/// Whether `Self` and `Other` are the same digit or the same identity.
+pub trait Same<Other> {
+    type Output;
+}
+
+pub trait And<Other> {
+    type Output;
+}
+
+impl<B> And<B> for True {
+    type Output = B;
+}
+
+impl<B> And<B> for False {
+    type Output = False;
+}
+
+impl<A0, A1, A2, B0, B1, B2> Same<(B0, B1, B2)> for (A0, A1, A2)
+where
+    A0: Same<B0>,
+    A1: Same<B1>,
+    A2: Same<B2>,
+    A0::Output: And<A1::Output>,
+    <A0::Output as And<A1::Output>>::Output: And<A2::Output>,
+{
+    type Output = <<A0::Output as And<A1::Output>>::Output as And<A2::Output>>::Output;
+}
+
+poem_extensions_macro::generate_slot_ids!();
+
+/// The identity of the slot key `K`.
+pub type IdOf<K> = <<K as SlotKey>::Key as SlotId>::Id;
+
+/// `Self` has no slot with the identity `Id`.
+pub trait Lacks<Id> {}
+
+impl<Id> Lacks<Id> for Empty {}
+
+impl<K, T, N, Id> Lacks<Id> for UniResponse<K, T, N>
+where
+    K: SlotKey,
+    T: Send,
+    IdOf<K>: Same<Id>,
+    Self: LacksAt<Id, <IdOf<K> as Same<Id>>::Output>,
+{
+}
+
+/// `Self` has no slot with the identity `Id`, where `S` is whether its first
+/// slot has it.
+#[diagnostic::on_unimplemented(
+    message = "duplicate slot in `UniResponse`",
+    label = "the first slot of `{Self}` is declared again",
+    note = "the slots of `response!` include the slots of every response set spread into it with `..`"
+)]
+pub trait LacksAt<Id, S> {}
+
+impl<K, T, N, Id> LacksAt<Id, False> for UniResponse<K, T, N>
+where
+    K: SlotKey,
+    T: Send,
+    N: Lacks<Id>,
+{
+}
@@ -0,0 +1,199 @@
+use std::fmt::{self, Display, Formatter};
+
+use poem::{error::ResponseError, http::StatusCode};
+use poem_openapi::registry::MetaResponse;
+
+use crate::slot_id::SlotId;
+
+/// The key of a [`UniResponse`](crate::UniResponse) slot.
+pub trait SlotKey {
+    /// The status code documented for this slot.
+    const STATUS: Option<u16>;
+
+    /// The status code range documented for this slot.
+    const STATUS_RANGE: Option<&'static str>;
+
+    /// The key used to select this slot, which is `Self` unless the slot is
+    /// [`Described`](crate::Described).
+    type Key: SlotId;
+
+    /// The value stored in this slot for a response of type `T`.
+    type Value<T>: Send
+    where
+        T: Send;
+
+    /// Splits the value into the status code to send and the response.
+    fn into_parts<T: Send>(value: Self::Value<T>) -> (StatusCode, T);
+
+    /// Checks that `status` can be sent from this slot.
+    fn check(_status: StatusCode) -> Result<(), SlotStatusError> {
+        Ok(())
+    }
+
+    /// Whether the errors of parsing the request can be sent from this slot,
+    /// which is false for the status code ranges other than `4XX`.
+    const BAD_REQUEST: bool = true;
+
+    /// Creates the value of a response `T` that is not given a status code,
+    /// e.g. the one made by its bad request handler.
+    fn from_value<T: Send>(value: T) -> Self::Value<T>;
+
+    /// Updates the documented response of this slot.
+    fn describe(_response: &mut MetaResponse) {}
+}
+
+/// The slot of a single status code, e.g. `Status<404>` for `404`.
+pub struct Status<const CODE: u16>;
+
+impl<const CODE: u16> SlotKey for Status<CODE>
+where
+    Self: SlotId,
+{
+    const STATUS: Option<u16> = Some(CODE);
+    const STATUS_RANGE: Option<&'static str> = None;
+
+    type Key = Self;
+
+    type Value<T>
+        = T
+    where
+        T: Send;
+
+    fn into_parts<T: Send>(value: T) -> (StatusCode, T) {
+        (StatusCode::from_u16(CODE).unwrap(), value)
+    }
+
+    fn from_value<T: Send>(value: T) -> T {
+        value
+    }
+}
+
+/// The slot of a status code range, e.g. `StatusRange<4>` for `4XX`. The
+/// value carries the status code to send, which must fall in the range.
+///
+/// A status code out of the range is rejected by `UniResponse::T4XX` etc. and
+/// sent as `500 Internal Server Error` otherwise.
+pub struct StatusRange<const CLASS: u16>;
+
+impl<const CLASS: u16> StatusRange<CLASS> {
+    pub fn contains(status: StatusCode) -> bool {
+        status.as_u16() / 100 == CLASS
+    }
+}
+
+impl<const CLASS: u16> SlotKey for StatusRange<CLASS>
+where
+    Self: SlotId,
+{
+    const STATUS: Option<u16> = None;
+    const STATUS_RANGE: Option<&'static str> = match CLASS {
+        1 => Some("1XX"),
+        2 => Some("2XX"),
+        3 => Some("3XX"),
+        4 => Some("4XX"),
+        5 => Some("5XX"),
+        _ => panic!("invalid status code range"),
+    };
+
+    const BAD_REQUEST: bool = CLASS == 4;
+
+    type Key = Self;
+
+    type Value<T>
+        = (StatusCode, T)
+    where
+        T: Send;
+
+    fn into_parts<T: Send>(value: (StatusCode, T)) -> (StatusCode, T) {
+        value
+    }
+
+    fn check(status: StatusCode) -> Result<(), SlotStatusError> {
+        if Self::contains(status) {
+            Ok(())
+        } else {
+            Err(SlotStatusError::OutOfRange {
+                status,
+                range: Self::STATUS_RANGE.unwrap_or_default(),
+            })
+        }
+    }
+
+    /// Sent with the first status code of the range, e.g. `400 Bad Request` in
+    /// `4XX`.
+    fn from_value<T: Send>(value: T) -> (StatusCode, T) {
+        (StatusCode::from_u16(CLASS * 100).unwrap(), value)
+    }
+}
+
+/// The `default` slot, which covers any status code that is not documented
+/// otherwise. The value carries the status code to send.
+pub struct DefaultStatus;
+
+impl SlotKey for DefaultStatus {
+    const STATUS: Option<u16> = None;
+    const STATUS_RANGE: Option<&'static str> = None;
+
+    type Key = Self;
+
+    type Value<T>
+        = (StatusCode, T)
+    where
+        T: Send;
+
+    fn into_parts<T: Send>(value: (StatusCode, T)) -> (StatusCode, T) {
+        value
+    }
+
+    fn from_value<T: Send>(value: T) -> (StatusCode, T) {
+        (StatusCode::BAD_REQUEST, value)
+    }
+}
+
+/// The status code given to a [`StatusRange`] or [`DefaultStatus`] slot
+/// cannot be sent from it.
+///
+/// It is a `500 Internal Server Error` when converted into `poem::Error`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SlotStatusError {
+    /// The status code is not in the range of the slot.
+    OutOfRange {
+        status: StatusCode,
+        range: &'static str,
+    },
+    /// The response carries another status code itself, e.g. in the
+    /// `#[oai(status)]` field of a `OneResponse` with `status_range`.
+    Conflict {
+        status: StatusCode,
+        response_status: StatusCode,
+    },
+}
+
+impl Display for SlotStatusError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SlotStatusError::OutOfRange { status, range } => write!(
+                f,
+                "status code {} is not in the range {range}",
+                status.as_u16()
+            ),
+            SlotStatusError::Conflict {
+                status,
+                response_status,
+            } => write!(
+                f,
+                "status code {} is not the status code {} of the response",
+                status.as_u16(),
+                response_status.as_u16()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SlotStatusError {}
+
+impl ResponseError for SlotStatusError {
+    fn status(&self) -> StatusCode {
+        StatusCode::INTERNAL_SERVER_ERROR
+    }
+}
@@ -1 +1,356 @@
+use std::marker::PhantomData;
+
+use poem::{http::StatusCode, IntoResponse, Response};
+use poem_openapi::{
+    registry::{MetaResponses, Registry},
+    ApiResponse,
+};
+
+use crate::{
+    slot::meta_helper,
+    slot_id::{False, IdOf, Lacks, Same, True},
+    Empty, IntoSlotResponse, OneResponse, Slot, SlotKey, SlotStatusError,
+};
+
+/// A list of responses keyed by slot, usually written with `response!`.
+///
+/// `response! { 200: A, 404: B }` expands to
+/// `UniResponse<Status<200>, A, UniResponse<Status<404>, B>>`, the list ends
+/// with [`Empty`].
+pub enum UniResponse<K: SlotKey, T: Send, N = Empty> {
+    Current(K::Value<T>),
+    Next(N),
+}
+
+/// Selects the first slot of a [`UniResponse`], inferred by the compiler.
+pub struct Here;
+
+/// Selects a slot further down a [`UniResponse`], the one that `I` selects in
+/// the rest of the list.
+pub struct There<I>(PhantomData<I>);
+
+/// Selects the `K` slot of a [`UniResponse`], where `I` is inferred.
+pub trait Select<K, I> {
+    /// The value stored in the slot.
+    type Value;
+
+    fn select(value: Self::Value) -> Self;
+
+    /// The value if the `K` slot is the chosen one.
+    fn get(&self) -> Option<&Self::Value>;
+
+    /// Takes the value if the `K` slot is the chosen one, otherwise gives the
+    /// response back.
+    fn take(self) -> Result<Self::Value, Self>
+    where
+        Self: Sized;
+}
+
+impl<K: SlotKey, T: Send, N> Select<K::Key, Here> for UniResponse<K, T, N> {
+    type Value = K::Value<T>;
+
+    fn select(value: Self::Value) -> Self {
+        UniResponse::Current(value)
+    }
+
+    fn get(&self) -> Option<&Self::Value> {
+        match self {
+            UniResponse::Current(value) => Some(value),
+            UniResponse::Next(_) => None,
+        }
+    }
+
+    fn take(self) -> Result<Self::Value, Self> {
+        match self {
+            UniResponse::Current(value) => Ok(value),
+            next => Err(next),
+        }
+    }
+}
+
+impl<K: SlotKey, T: Send, N, K2, I> Select<K2, There<I>> for UniResponse<K, T, N>
+where
+    N: Select<K2, I>,
+{
+    type Value = N::Value;
+
+    fn select(value: Self::Value) -> Self {
+        UniResponse::Next(N::select(value))
+    }
+
+    fn get(&self) -> Option<&Self::Value> {
+        match self {
+            UniResponse::Current(_) => None,
+            UniResponse::Next(next) => next.get(),
+        }
+    }
+
+    fn take(self) -> Result<Self::Value, Self> {
+        match self {
+            UniResponse::Current(value) => Err(UniResponse::Current(value)),
+            UniResponse::Next(next) => next.take().map_err(UniResponse::Next),
+        }
+    }
+}
+
+impl<K: SlotKey, T: Send, N> UniResponse<K, T, N> {
+    /// Creates a response of any status code, e.g.
+    /// `UniResponse::with_status::<499, _>(value)`.
+    pub fn with_status<const CODE: u16, I>(
+        value: <Self as Select<crate::Status<CODE>, I>>::Value,
+    ) -> Self
+    where
+        Self: Select<crate::Status<CODE>, I>,
+    {
+        Self::select(value)
+    }
+
+    /// The value of the slot `S` if it is the chosen one, e.g.
+    /// `resp.get::<Status<200>, _>()`.
+    pub fn get<S, I>(&self) -> Option<&<Self as Select<S, I>>::Value>
+    where
+        Self: Select<S, I>,
+    {
+        Select::get(self)
+    }
+
+    /// Takes the value of the slot `S` if it is the chosen one, e.g.
+    /// `resp.take::<Status<200>, _>()`, otherwise gives the response back.
+    pub fn take<S, I>(self) -> Result<<Self as Select<S, I>>::Value, Self>
+    where
+        Self: Select<S, I>,
+    {
+        Select::take(self)
+    }
+}
+
+/// A slot of any status code, `OtherStatus<499, T, N>` is
+/// `UniResponse<Status<499>, T, N>`.
+pub type OtherStatus<const CODE: u16, T, N = Empty> = UniResponse<crate::Status<CODE>, T, N>;
+
 poem_extensions_macro::generate_define_uni_response!();
+
+/// Inserts the response `X` into the slot of its status code.
+#[diagnostic::on_unimplemented(
+    message = "there is no slot of the status code of `{X}`",
+    label = "`{X}` cannot be converted into this `UniResponse`",
+    note = "a `OneResponse` converts into the slot declared with its status code in `response!`"
+)]
+pub trait Insert<X>: Sized {
+    fn insert(value: X) -> Self;
+}
+
+impl<K, T, N, X> Insert<X> for UniResponse<K, T, N>
+where
+    K: SlotKey,
+    T: Send,
+    X: OneResponse,
+    IdOf<K>: Same<IdOf<X::Key>>,
+    Self: InsertAt<X, <IdOf<K> as Same<IdOf<X::Key>>>::Output>,
+{
+    fn insert(value: X) -> Self {
+        Self::insert_at(value)
+    }
+}
+
+/// Inserts the response `X` into `Self`, where `S` is whether the first slot
+/// has the status code of `X`.
+#[diagnostic::on_unimplemented(
+    message = "the slot of the status code of `{X}` holds another type",
+    label = "`{X}` is not the response of this slot",
+    note = "wrap the response in the type of the slot, e.g. `KeepStatus(..)`, and create it with the constructor of the slot"
+)]
+pub trait InsertAt<X, S>: Sized {
+    fn insert_at(value: X) -> Self;
+}
+
+impl<K: SlotKey, T: Send, N> InsertAt<T, True> for UniResponse<K, T, N> {
+    fn insert_at(value: T) -> Self {
+        UniResponse::Current(K::from_value(value))
+    }
+}
+
+impl<K, T, N, X> InsertAt<X, False> for UniResponse<K, T, N>
+where
+    K: SlotKey,
+    T: Send,
+    N: Insert<X>,
+{
+    fn insert_at(value: X) -> Self {
+        UniResponse::Next(N::insert(value))
+    }
+}
+
+/// A [`OneResponse`] converts into the slot of its status code, e.g.
+/// `BadRequest.into()` or `?`.
+impl<K, T, N, X> From<X> for UniResponse<K, T, N>
+where
+    K: SlotKey,
+    T: Send,
+    X: OneResponse,
+    Self: Insert<X>,
+{
+    fn from(value: X) -> Self {
+        Self::insert(value)
+    }
+}
+
+/// Converts a [`UniResponse`] into a wider `W` that has all of its slots,
+/// where `I` is inferred.
+///
+/// A narrower response cannot convert with `.into()` or `?` itself, as that
+/// would overlap with `impl<T> From<T> for T`, so the error of a `Result` is
+/// widened with [`WidenResult::widen`] first, e.g. `find_user(id).widen()?`.
+/// For the same reason a payload type such as `Json<T>` is put in its slot
+/// with the constructor of the slot, e.g. `T404(..)`, and not with `.into()`.
+pub trait Widen<W, I> {
+    fn widen(self) -> W;
+}
+
+impl<W> Widen<W, Here> for Empty {
+    fn widen(self) -> W {
+        match self {}
+    }
+}
+
+impl<K, T, N, W, I, Is> Widen<W, (I, Is)> for UniResponse<K, T, N>
+where
+    K: SlotKey,
+    T: Send,
+    W: Select<K::Key, I, Value = K::Value<T>>,
+    N: Widen<W, Is>,
+{
+    fn widen(self) -> W {
+        match self {
+            UniResponse::Current(value) => W::select(value),
+            UniResponse::Next(next) => next.widen(),
+        }
+    }
+}
+
+/// The error of a `Result` on its way into a wider [`UniResponse`], which `?`
+/// converts with `From`.
+pub struct Widened<E, I>(E, PhantomData<I>);
+
+impl<K, T, N, E, I> From<Widened<E, I>> for UniResponse<K, T, N>
+where
+    K: SlotKey,
+    T: Send,
+    E: Widen<Self, I>,
+{
+    fn from(widened: Widened<E, I>) -> Self {
+        widened.0.widen()
+    }
+}
+
+/// Widens the error of a `Result` into the error type of the function it is
+/// returned from with `?`.
+pub trait WidenResult<T, E> {
+    /// `find_user(id).widen()?`, where `find_user` returns a narrower
+    /// [`UniResponse`].
+    fn widen<I>(self) -> Result<T, Widened<E, I>>;
+}
+
+impl<T, E> WidenResult<T, E> for Result<T, E> {
+    fn widen<I>(self) -> Result<T, Widened<E, I>> {
+        self.map_err(|err| Widened(err, PhantomData))
+    }
+}
+
+impl<K, T, N> ApiResponse for UniResponse<K, T, N>
+where
+    K: SlotKey,
+    T: ApiResponse + IntoSlotResponse + Slot<K::Key> + 'static,
+    N: ApiResponse + Lacks<IdOf<K>>,
+{
+    /// The first slot whose response has a bad request handler handles the
+    /// errors of parsing the request, a `1XX`, `2XX`, `3XX` or `5XX` slot never
+    /// does.
+    const BAD_REQUEST_HANDLER: bool =
+        (K::BAD_REQUEST && T::BAD_REQUEST_HANDLER) || N::BAD_REQUEST_HANDLER;
+
+    fn meta() -> MetaResponses {
+        let mut responses = Vec::new();
+        meta_helper::<T>(&mut responses, K::STATUS, K::STATUS_RANGE);
+        responses.iter_mut().for_each(K::describe);
+        responses.extend(N::meta().responses);
+        MetaResponses { responses }
+    }
+
+    fn register(registry: &mut Registry) {
+        T::register(registry);
+        N::register(registry);
+    }
+
+    fn from_parse_request_error(err: poem::Error) -> Self {
+        if K::BAD_REQUEST && T::BAD_REQUEST_HANDLER {
+            UniResponse::Current(K::from_value(T::from_parse_request_error(err)))
+        } else {
+            UniResponse::Next(N::from_parse_request_error(err))
+        }
+    }
+}
+
+impl<K, T, N> IntoResponse for UniResponse<K, T, N>
+where
+    K: SlotKey,
+    T: IntoSlotResponse + Slot<K::Key>,
+    N: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        match self {
+            UniResponse::Current(value) => match slot_parts::<K, T>(value) {
+                Ok((status, value)) => value.into_slot_response(status),
+                Err(err) => poem::Error::from(err).into_response(),
+            },
+            UniResponse::Next(next) => next.into_response(),
+        }
+    }
+}
+
+impl<K, T, N> From<UniResponse<K, T, N>> for poem::Error
+where
+    K: SlotKey,
+    T: IntoSlotResponse + Slot<K::Key>,
+    N: Into<poem::Error>,
+{
+    fn from(resp: UniResponse<K, T, N>) -> Self {
+        match resp {
+            UniResponse::Current(value) => match slot_parts::<K, T>(value) {
+                Ok((status, value)) => value.into_slot_error(status),
+                Err(err) => err.into(),
+            },
+            UniResponse::Next(next) => next.into(),
+        }
+    }
+}
+
+/// Splits the slot value into the status code to send and the response, the
+/// status code carried by the response itself takes precedence.
+fn slot_parts<K, T>(value: K::Value<T>) -> Result<(StatusCode, T), SlotStatusError>
+where
+    K: SlotKey,
+    T: IntoSlotResponse,
+{
+    let (status, value) = K::into_parts(value);
+    let status = value.status().unwrap_or(status);
+    K::check(status)?;
+    Ok((status, value))
+}
+
+/// Checks that `status` can be sent from the slot `K`, and that it is the
+/// status code `value` carries itself, if any.
+fn check_slot_status<K, T>(status: StatusCode, value: &T) -> Result<(), SlotStatusError>
+where
+    K: SlotKey,
+    T: IntoSlotResponse,
+{
+    K::check(status)?;
+    match value.status() {
+        Some(response_status) if response_status != status => Err(SlotStatusError::Conflict {
+            status,
+            response_status,
+        }),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,134 @@
+use poem::{http::StatusCode, test::TestClient};
+use poem_extensions::{response, ErrorResponse, OneResponse, UniResponse};
+use poem_openapi::{
+    param::Path,
+    payload::{Json, PlainText},
+    registry::MetaApi,
+    OpenApi, OpenApiService,
+};
+
+/// Not found
+#[derive(OneResponse, Default)]
+#[oai(status = 404)]
+struct NotFound;
+
+/// Conflict
+#[derive(OneResponse)]
+#[oai(status = 409)]
+struct Conflict(PlainText<String>);
+
+impl From<String> for Conflict {
+    fn from(name: String) -> Self {
+        Conflict(PlainText(format!("{name} is taken")))
+    }
+}
+
+fn internal(code: u16, message: &'static str) -> PlainText<String> {
+    PlainText(format!("{code}: {message}"))
+}
+
+#[derive(Debug, ErrorResponse)]
+enum UserError {
+    #[oai(status = 404, response = "NotFound")]
+    NotFound,
+    // the same response type, written as another path
+    #[oai(status = NOT_FOUND, response = "self::NotFound")]
+    Deleted,
+    #[oai(status = 409, response = "Conflict")]
+    NameTaken { name: String },
+    #[oai(status = 500, response = "PlainText<String>", with = "internal")]
+    Internal(u16, &'static str),
+}
+
+type ApiErrors = response! {
+    400: PlainText<String>,
+    404: NotFound,
+    409: Conflict,
+    500: PlainText<String>,
+};
+
+#[derive(Debug, ErrorResponse)]
+#[oai(into = "ApiErrors")]
+enum OrderError {
+    #[oai(status = 404, response = "NotFound")]
+    NotFound,
+}
+
+fn find_user(id: u32) -> Result<String, UserError> {
+    match id {
+        0 => Err(UserError::NotFound),
+        1 => Err(UserError::Deleted),
+        2 => Err(UserError::NameTaken {
+            name: "sunli".to_string(),
+        }),
+        3 => Err(UserError::Internal(7, "database")),
+        _ => Ok(format!("user {id}")),
+    }
+}
+
+fn find_order(id: u32) -> Result<String, OrderError> {
+    match id {
+        0 => Err(OrderError::NotFound),
+        _ => Ok(format!("order {id}")),
+    }
+}
+
+#[tokio::test]
+async fn error_response() {
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/users/:id", method = "get")]
+        async fn user(
+            &self,
+            id: Path<u32>,
+        ) -> Result<response! { 200: Json<String> }, UserErrorResponse> {
+            Ok(UniResponse::T200(Json(find_user(id.0)?)))
+        }
+
+        #[oai(path = "/orders/:id", method = "get")]
+        async fn order(&self, id: Path<u32>) -> Result<response! { 200: Json<String> }, ApiErrors> {
+            Ok(UniResponse::T200(Json(find_order(id.0)?)))
+        }
+    }
+
+    let meta: MetaApi = Api::meta().remove(0);
+    assert_eq!(
+        meta.paths[0].operations[0]
+            .responses
+            .responses
+            .iter()
+            .map(|resp| resp.status)
+            .collect::<Vec<_>>(),
+        [Some(200), Some(404), Some(409), Some(500)]
+    );
+
+    let ep = OpenApiService::new(Api, "test", "1.0");
+    let cli = TestClient::new(ep);
+
+    cli.get("/users/0")
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    cli.get("/users/1")
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+
+    let resp = cli.get("/users/2").send().await;
+    resp.assert_status(StatusCode::CONFLICT);
+    resp.assert_text("sunli is taken").await;
+
+    let resp = cli.get("/users/3").send().await;
+    resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    resp.assert_text("7: database").await;
+
+    cli.get("/users/4").send().await.assert_status_is_ok();
+
+    cli.get("/orders/0")
+        .send()
+        .await
+        .assert_status(StatusCode::NOT_FOUND);
+    cli.get("/orders/1").send().await.assert_status_is_ok();
+}
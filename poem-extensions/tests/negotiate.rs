@@ -0,0 +1,125 @@
+use poem::{http::StatusCode, test::TestClient, web::Accept};
+use poem_extensions::{response, Either, Negotiate, NotAcceptable, Representation, UniResponse};
+use poem_openapi::{
+    payload::{Json, PlainText},
+    registry::MetaApi,
+    Object, OpenApi, OpenApiService,
+};
+
+#[derive(Object)]
+struct User {
+    name: String,
+}
+
+impl Representation<User> for PlainText<String> {
+    fn represent(value: User) -> Self {
+        PlainText(value.name)
+    }
+}
+
+fn user() -> User {
+    User {
+        name: "sunli".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn negotiate() {
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/user", method = "get")]
+        async fn user(
+            &self,
+            accept: Accept,
+        ) -> Result<Either<Json<User>, PlainText<String>>, NotAcceptable> {
+            Negotiate::negotiate(&accept, user())
+        }
+    }
+
+    let ep = OpenApiService::new(Api, "test", "1.0");
+    let cli = TestClient::new(ep);
+
+    let resp = cli.get("/user").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_content_type("application/json; charset=utf-8");
+    resp.assert_text(r#"{"name":"sunli"}"#).await;
+
+    let resp = cli
+        .get("/user")
+        .header("accept", "text/plain, application/json;q=0.5")
+        .send()
+        .await;
+    resp.assert_status_is_ok();
+    resp.assert_content_type("text/plain; charset=utf-8");
+    resp.assert_text("sunli").await;
+
+    let resp = cli.get("/user").header("accept", "text/*").send().await;
+    resp.assert_content_type("text/plain; charset=utf-8");
+
+    let resp = cli
+        .get("/user")
+        .header("accept", "application/json;q=0, */*;q=0.1")
+        .send()
+        .await;
+    resp.assert_content_type("text/plain; charset=utf-8");
+
+    let resp = cli.get("/user").header("accept", "image/png").send().await;
+    resp.assert_status(StatusCode::NOT_ACCEPTABLE);
+
+    let meta: MetaApi = Api::meta().remove(0);
+    let responses = &meta.paths[0].operations[0].responses.responses;
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0].status, Some(200));
+    assert_eq!(
+        responses[0]
+            .content
+            .iter()
+            .map(|media_type| media_type.content_type)
+            .collect::<Vec<_>>(),
+        [
+            "application/json; charset=utf-8",
+            "text/plain; charset=utf-8"
+        ]
+    );
+    assert_eq!(responses[1].status, Some(406));
+}
+
+#[tokio::test]
+async fn uni_response() {
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/user", method = "get")]
+        async fn user(
+            &self,
+            accept: Accept,
+        ) -> response! {
+               200: Json<User> | PlainText<String>,
+               406: NotAcceptable,
+           } {
+            match Negotiate::negotiate(&accept, user()) {
+                Ok(payload) => UniResponse::T200(payload),
+                Err(err) => UniResponse::T406(err),
+            }
+        }
+    }
+
+    let ep = OpenApiService::new(Api, "test", "1.0");
+    let cli = TestClient::new(ep);
+
+    let resp = cli.get("/user").header("accept", "text/plain").send().await;
+    resp.assert_status_is_ok();
+    resp.assert_text("sunli").await;
+
+    let resp = cli.get("/user").header("accept", "text/html").send().await;
+    resp.assert_status(StatusCode::NOT_ACCEPTABLE);
+
+    let meta: MetaApi = Api::meta().remove(0);
+    let responses = &meta.paths[0].operations[0].responses.responses;
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0].content.len(), 2);
+    assert_eq!(responses[1].status, Some(406));
+}
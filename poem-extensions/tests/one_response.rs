@@ -5,9 +5,9 @@ use poem::{
     test::TestClient,
     Error, IntoResponse,
 };
-use poem_extensions::OneResponse;
+use poem_extensions::{Either, ErrorMetadata, OneResponse};
 use poem_openapi::{
-    payload::{Binary, Json, Payload},
+    payload::{Binary, Json, Payload, PlainText},
     registry::{
         MetaApi, MetaMediaType, MetaResponse, MetaResponses, MetaSchema, MetaSchemaRef, Registry,
     },
@@ -67,6 +67,12 @@ fn meta() {
     );
 }
 
+#[test]
+fn status() {
+    assert_eq!(Ok::STATUS, 200);
+    assert_eq!(BadRequest::STATUS, 400);
+}
+
 #[tokio::test]
 async fn into_response() {
     let resp = Ok.into_response();
@@ -293,13 +299,86 @@ async fn extra_headers_on_response() {
     assert!(!meta.responses[0].headers[1].deprecated);
     assert_eq!(meta.responses[0].headers[1].schema, String::schema_ref());
 
-    assert_eq!(meta.responses[0].headers[2].name, "A2");
+    assert_eq!(meta.responses[0].headers[2].name, "a2");
     assert_eq!(
         meta.responses[0].headers[2].description.as_deref(),
         Some("abc")
     );
     assert!(meta.responses[0].headers[2].deprecated);
     assert_eq!(meta.responses[0].headers[2].schema, i32::schema_ref());
+
+    let resp = A(Json(1), "a".to_string()).into_response();
+    assert!(!resp.headers().contains_key("A1"));
+}
+
+#[tokio::test]
+async fn extra_header_values() {
+    fn request_id() -> Option<String> {
+        Some("r-1".to_string())
+    }
+
+    #[derive(OneResponse)]
+    #[oai(
+        status = 200,
+        header(name = "X-Version", ty = "String", value = "1.0"),
+        header(name = "X-Limit", ty = "i32", value = 100),
+        header(name = "X-Request-Id", ty = "Option<String>", with = "request_id")
+    )]
+    enum A {
+        Json(Json<i32>),
+        Empty,
+    }
+
+    for resp in [A::Json(Json(1)).into_response(), A::Empty.into_response()] {
+        assert_eq!(
+            resp.headers().get("X-VERSION"),
+            Some(&HeaderValue::from_static("1.0"))
+        );
+        assert_eq!(
+            resp.headers().get("X-LIMIT"),
+            Some(&HeaderValue::from_static("100"))
+        );
+        assert_eq!(
+            resp.headers().get("X-REQUEST-ID"),
+            Some(&HeaderValue::from_static("r-1"))
+        );
+    }
+
+    let err: Error = A::Empty.into();
+    assert_eq!(
+        err.into_response().headers().get("X-VERSION"),
+        Some(&HeaderValue::from_static("1.0"))
+    );
+}
+
+#[test]
+fn extra_header_literal_types() {
+    #[derive(OneResponse)]
+    #[oai(
+        status = 200,
+        header(name = "X-Max-Size", ty = "u64", value = 1048576),
+        header(name = "X-Region", ty = "String", value = "eu"),
+        header(name = "X-Opt", ty = "Option<String>", value = "v"),
+        header(name = "X-Retry", ty = "i32", value = "100"),
+        header(name = "X-Offset", ty = "i64", value = "-5"),
+        header(name = "X-Ratio", ty = "Option<f64>", value = "0.5")
+    )]
+    struct A;
+
+    let resp = A.into_response();
+    assert_eq!(resp.headers()["X-MAX-SIZE"], "1048576");
+    assert_eq!(resp.headers()["X-REGION"], "eu");
+    assert_eq!(resp.headers()["X-OPT"], "v");
+    assert_eq!(resp.headers()["X-RETRY"], "100");
+    assert_eq!(resp.headers()["X-OFFSET"], "-5");
+    assert_eq!(resp.headers()["X-RATIO"], "0.5");
+
+    #[derive(OneResponse)]
+    #[oai(status = 200, header(name = "X-Limit", ty = "Option<u32>", value = 7))]
+    struct B;
+
+    let resp = B.into_response();
+    assert_eq!(resp.headers()["X-LIMIT"], "7");
 }
 
 #[tokio::test]
@@ -419,3 +498,619 @@ async fn actual_type() {
     let type_name: Vec<&String> = registry.schemas.keys().collect();
     assert_eq!(&type_name, &["MyObj"]);
 }
+
+#[tokio::test]
+async fn status_range() {
+    /// Client error
+    #[derive(OneResponse)]
+    #[oai(status_range = "4XX")]
+    struct ClientError(StatusCode, Json<BadRequestResult>);
+
+    #[derive(OneResponse)]
+    #[oai(status_range = "5XX")]
+    struct ServerError(StatusCode, #[oai(header = "MY-HEADER")] i32);
+
+    assert_eq!(
+        ClientError::meta(),
+        MetaResponses {
+            responses: vec![MetaResponse {
+                description: "Client error",
+                status: None,
+                status_range: Some("4XX".to_string()),
+                content: vec![MetaMediaType {
+                    content_type: "application/json; charset=utf-8",
+                    schema: MetaSchemaRef::Reference("BadRequestResult".to_string())
+                }],
+                headers: vec![]
+            }],
+        },
+    );
+
+    let meta: MetaResponses = ServerError::meta();
+    assert_eq!(meta.responses[0].status, None);
+    assert_eq!(meta.responses[0].status_range.as_deref(), Some("5XX"));
+    assert_eq!(meta.responses[0].headers[0].name, "MY-HEADER");
+
+    let mut resp = ClientError(
+        StatusCode::CONFLICT,
+        Json(BadRequestResult {
+            error_code: 1,
+            message: "conflict".to_string(),
+        }),
+    )
+    .into_response();
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    assert_eq!(
+        serde_json::from_slice::<Value>(&resp.take_body().into_bytes().await.unwrap()).unwrap(),
+        serde_json::json!({
+            "error_code": 1,
+            "message": "conflict",
+        })
+    );
+
+    let resp = ServerError(StatusCode::SERVICE_UNAVAILABLE, 1).into_response();
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(
+        resp.headers().get("MY-HEADER"),
+        Some(&HeaderValue::from_static("1"))
+    );
+}
+
+#[tokio::test]
+async fn other_status() {
+    #[derive(OneResponse)]
+    #[oai(status = 499)]
+    struct ClientClosedRequest;
+
+    assert_eq!(ClientClosedRequest::STATUS, 499);
+
+    let meta: MetaResponses = ClientClosedRequest::meta();
+    assert_eq!(meta.responses[0].status, Some(499));
+
+    let resp = ClientClosedRequest.into_response();
+    assert_eq!(resp.status().as_u16(), 499);
+}
+
+#[tokio::test]
+async fn status_range_mismatch() {
+    #[derive(OneResponse)]
+    #[oai(status_range = "5XX")]
+    struct ServerError(StatusCode);
+
+    let mut resp = ServerError(StatusCode::OK).into_response();
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(
+        resp.take_body().into_string().await.unwrap(),
+        "status code 200 is not in the range 5XX"
+    );
+}
+
+#[tokio::test]
+async fn multiple_content_types() {
+    #[derive(OneResponse)]
+    #[oai(status = 200)]
+    struct A(Either<Json<i32>, Binary<Vec<u8>>>);
+
+    let responses = A::meta().responses;
+    assert_eq!(responses.len(), 1);
+    assert_eq!(
+        responses[0]
+            .content
+            .iter()
+            .map(|media_type| media_type.content_type)
+            .collect::<Vec<_>>(),
+        [
+            "application/json; charset=utf-8",
+            "application/octet-stream"
+        ]
+    );
+
+    let resp = A(Either::Left(Json(1))).into_response();
+    assert_eq!(resp.content_type(), Some("application/json; charset=utf-8"));
+
+    let resp = A(Either::Right(Binary(vec![1]))).into_response();
+    assert_eq!(resp.content_type(), Some("application/octet-stream"));
+}
+
+#[test]
+fn named_status() {
+    #[derive(OneResponse)]
+    #[oai(status = TOO_MANY_REQUESTS)]
+    struct TooManyRequests;
+
+    assert_eq!(TooManyRequests::STATUS, 429);
+    assert_eq!(
+        TooManyRequests.into_response().status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+}
+
+#[tokio::test]
+async fn enum_variants() {
+    /// Bad request
+    #[derive(OneResponse)]
+    #[oai(status = 400)]
+    enum BadRequest {
+        /// Validation failed
+        Validation(Json<Vec<String>>),
+        Rule(Json<BadRequestResult>, #[oai(header = "X-Rule")] String),
+        Message(PlainText<String>),
+        Empty,
+    }
+
+    assert_eq!(BadRequest::STATUS, 400);
+
+    let responses = BadRequest::meta().responses;
+    assert_eq!(responses.len(), 1);
+    assert_eq!(responses[0].description, "Bad request");
+    assert_eq!(
+        responses[0].content,
+        vec![
+            MetaMediaType {
+                content_type: "application/json; charset=utf-8",
+                schema: MetaSchemaRef::Inline(Box::new(MetaSchema {
+                    one_of: vec![<Vec<String>>::schema_ref(), BadRequestResult::schema_ref()],
+                    ..MetaSchema::ANY
+                })),
+            },
+            MetaMediaType {
+                content_type: "text/plain; charset=utf-8",
+                schema: String::schema_ref(),
+            },
+        ]
+    );
+    assert_eq!(responses[0].headers.len(), 1);
+    assert_eq!(responses[0].headers[0].name, "X-Rule");
+    assert!(!responses[0].headers[0].required);
+
+    let mut registry = Registry::new();
+    BadRequest::register(&mut registry);
+    assert!(registry.schemas.contains_key("BadRequestResult"));
+
+    let mut resp = BadRequest::Validation(Json(vec!["name".to_string()])).into_response();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(resp.take_body().into_string().await.unwrap(), r#"["name"]"#);
+
+    let resp = BadRequest::Rule(
+        Json(BadRequestResult {
+            error_code: 1,
+            message: "rule".to_string(),
+        }),
+        "unique".to_string(),
+    )
+    .into_response();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(
+        resp.headers().get("X-RULE"),
+        Some(&HeaderValue::from_static("unique"))
+    );
+
+    let mut resp = BadRequest::Message(PlainText("oops".to_string())).into_response();
+    assert_eq!(resp.content_type(), Some("text/plain; charset=utf-8"));
+    assert_eq!(resp.take_body().into_string().await.unwrap(), "oops");
+
+    let resp = BadRequest::Empty.into_response();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let err: Error = BadRequest::Validation(Json(vec![])).into();
+    assert_eq!(err.to_string(), "Validation failed");
+    let err: Error = BadRequest::Empty.into();
+    assert_eq!(err.to_string(), "Bad request");
+}
+
+#[tokio::test]
+async fn named_fields() {
+    #[derive(OneResponse)]
+    #[oai(status = 201)]
+    struct Created {
+        /// where to find it
+        location: String,
+        #[oai(body)]
+        body: Json<BadRequestResult>,
+        #[oai(header = "X-Trace")]
+        trace_id: String,
+        x_rate_limit: Option<u32>,
+    }
+
+    let meta: MetaResponses = Created::meta();
+    let headers = &meta.responses[0].headers;
+    assert_eq!(
+        headers
+            .iter()
+            .map(|header| header.name.as_str())
+            .collect::<Vec<_>>(),
+        ["Location", "X-Trace", "X-Rate-Limit"]
+    );
+    assert_eq!(headers[0].description.as_deref(), Some("where to find it"));
+    assert!(!headers[2].required);
+    assert_eq!(
+        meta.responses[0].content[0].schema,
+        BadRequestResult::schema_ref()
+    );
+
+    let mut resp = Created {
+        location: "/users/1".to_string(),
+        body: Json(BadRequestResult {
+            error_code: 0,
+            message: "created".to_string(),
+        }),
+        trace_id: "abc".to_string(),
+        x_rate_limit: Some(10),
+    }
+    .into_response();
+    assert_eq!(resp.status(), StatusCode::CREATED);
+    assert_eq!(
+        resp.headers().get("LOCATION"),
+        Some(&HeaderValue::from_static("/users/1"))
+    );
+    assert_eq!(
+        resp.headers().get("X-TRACE"),
+        Some(&HeaderValue::from_static("abc"))
+    );
+    assert_eq!(
+        resp.headers().get("X-RATE-LIMIT"),
+        Some(&HeaderValue::from_static("10"))
+    );
+    assert_eq!(
+        serde_json::from_slice::<Value>(&resp.take_body().into_bytes().await.unwrap()).unwrap(),
+        serde_json::json!({
+            "error_code": 0,
+            "message": "created",
+        })
+    );
+
+    #[derive(OneResponse)]
+    #[oai(status_range = "5XX")]
+    struct ServerError {
+        retry_after: u32,
+        #[oai(status)]
+        status: StatusCode,
+    }
+
+    let resp = ServerError {
+        retry_after: 5,
+        status: StatusCode::BAD_GATEWAY,
+    }
+    .into_response();
+    assert_eq!(resp.status(), StatusCode::BAD_GATEWAY);
+    assert_eq!(
+        resp.headers().get("RETRY-AFTER"),
+        Some(&HeaderValue::from_static("5"))
+    );
+}
+
+#[test]
+fn header_case() {
+    #[derive(OneResponse)]
+    #[oai(status = 200, header(name = "X-Version", ty = "String"))]
+    struct Declared(#[oai(header = "X-Request-Id")] String);
+
+    let meta: MetaResponses = Declared::meta();
+    assert_eq!(meta.responses[0].headers[0].name, "X-Request-Id");
+    assert_eq!(meta.responses[0].headers[1].name, "X-Version");
+
+    #[derive(OneResponse)]
+    #[oai(
+        status = 200,
+        header_case = "lower",
+        header(name = "X-Version", ty = "String")
+    )]
+    struct Lower {
+        request_id: String,
+    }
+
+    let meta: MetaResponses = Lower::meta();
+    assert_eq!(meta.responses[0].headers[0].name, "request-id");
+    assert_eq!(meta.responses[0].headers[1].name, "x-version");
+
+    #[derive(OneResponse)]
+    #[oai(status = 200, header_case = "upper")]
+    struct Upper(#[oai(header = "X-Request-Id")] String);
+
+    let meta: MetaResponses = Upper::meta();
+    assert_eq!(meta.responses[0].headers[0].name, "X-REQUEST-ID");
+
+    let resp = Upper("a".to_string()).into_response();
+    assert_eq!(
+        resp.headers().get("x-request-id"),
+        Some(&HeaderValue::from_static("a"))
+    );
+}
+
+#[test]
+fn multi_valued_headers() {
+    #[derive(OneResponse)]
+    #[oai(status = 200)]
+    struct Page {
+        #[oai(body)]
+        body: Json<Vec<i32>>,
+        link: Vec<String>,
+        #[oai(header = "Vary")]
+        vary: Vec<String>,
+    }
+
+    let meta: MetaResponses = Page::meta();
+    let link = &meta.responses[0].headers[0];
+    assert_eq!(link.name, "Link");
+    assert!(!link.required);
+    assert_eq!(link.schema, <Vec<String>>::schema_ref());
+    assert!(link.schema.is_array());
+
+    let resp = Page {
+        body: Json(vec![1]),
+        link: vec![
+            r#"</items?page=2>; rel="next""#.to_string(),
+            r#"</items?page=9>; rel="last""#.to_string(),
+        ],
+        vary: vec![],
+    }
+    .into_response();
+    assert_eq!(
+        resp.headers()
+            .get_all("link")
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>(),
+        [
+            r#"</items?page=2>; rel="next""#,
+            r#"</items?page=9>; rel="last""#
+        ]
+    );
+    assert!(!resp.headers().contains_key("vary"));
+}
+
+#[test]
+fn optional_multi_valued_headers() {
+    #[derive(OneResponse)]
+    #[oai(status = 200)]
+    struct Page {
+        #[oai(body)]
+        body: Json<Vec<i32>>,
+        link: Option<Vec<String>>,
+    }
+
+    let meta: MetaResponses = Page::meta();
+    let link = &meta.responses[0].headers[0];
+    assert_eq!(link.name, "Link");
+    assert!(!link.required);
+    assert!(link.schema.is_array());
+
+    let resp = Page {
+        body: Json(vec![1]),
+        link: Some(vec!["</a>".to_string(), "</b>".to_string()]),
+    }
+    .into_response();
+    assert_eq!(
+        resp.headers()
+            .get_all("link")
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>(),
+        ["</a>", "</b>"]
+    );
+
+    let resp = Page {
+        body: Json(vec![1]),
+        link: None,
+    }
+    .into_response();
+    assert!(!resp.headers().contains_key("link"));
+}
+
+#[test]
+fn cookies() {
+    #[derive(OneResponse)]
+    #[oai(status = 200)]
+    struct Login {
+        #[oai(body)]
+        body: Json<i32>,
+        /// the session id
+        #[oai(
+            cookie = "session",
+            http_only,
+            secure,
+            same_site = "strict",
+            max_age = 3600,
+            path = "/"
+        )]
+        session: String,
+        #[oai(cookie = "csrf")]
+        csrf: Option<String>,
+    }
+
+    let meta: MetaResponses = Login::meta();
+    let headers = &meta.responses[0].headers;
+    assert_eq!(headers.len(), 1);
+    assert_eq!(headers[0].name, "Set-Cookie");
+    assert_eq!(
+        headers[0].description.as_deref(),
+        Some(
+            "`session` (HttpOnly; Secure; SameSite=Strict; Max-Age=3600; Path=/): the session id\n`csrf`"
+        )
+    );
+    assert!(headers[0].required);
+    assert_eq!(headers[0].schema, String::schema_ref());
+
+    let resp = Login {
+        body: Json(1),
+        session: "a b;c".to_string(),
+        csrf: Some("token".to_string()),
+    }
+    .into_response();
+    assert_eq!(
+        resp.headers()
+            .get_all("set-cookie")
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>(),
+        [
+            "session=a%20b%3Bc; HttpOnly; Secure; SameSite=Strict; Max-Age=3600; Path=/",
+            "csrf=token"
+        ]
+    );
+
+    let resp = Login {
+        body: Json(1),
+        session: "a".to_string(),
+        csrf: None,
+    }
+    .into_response();
+    assert_eq!(resp.headers().get_all("set-cookie").iter().count(), 1);
+}
+
+#[test]
+fn cookie_value_round_trip() {
+    #[derive(OneResponse)]
+    #[oai(status = 200)]
+    struct Login {
+        #[oai(cookie = "session")]
+        session: String,
+    }
+
+    fn percent_decode(value: &str) -> String {
+        let mut bytes = Vec::new();
+        let mut rest = value.as_bytes();
+        while let Some((&b, tail)) = rest.split_first() {
+            if b == b'%' {
+                let hex = std::str::from_utf8(&tail[..2]).unwrap();
+                bytes.push(u8::from_str_radix(hex, 16).unwrap());
+                rest = &tail[2..];
+            } else {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+        String::from_utf8(bytes).unwrap()
+    }
+
+    for session in ["100%", "%20", "a b;c,\\\"d\"", "%%41"] {
+        let resp = Login {
+            session: session.to_string(),
+        }
+        .into_response();
+        let cookie = resp.headers()["set-cookie"].to_str().unwrap();
+        let value = cookie.strip_prefix("session=").unwrap();
+        assert_eq!(percent_decode(value), session);
+    }
+
+    let resp = Login {
+        session: "100%".to_string(),
+    }
+    .into_response();
+    assert_eq!(resp.headers()["set-cookie"], "session=100%25");
+}
+
+#[test]
+fn error_message() {
+    /// User not found
+    #[derive(OneResponse)]
+    #[oai(
+        status = 404,
+        error_message = "user {0.error_code} not found: {0.message:?}"
+    )]
+    struct UserNotFound(Json<BadRequestResult>);
+
+    let err: Error = UserNotFound(Json(BadRequestResult {
+        error_code: 7,
+        message: "gone".to_string(),
+    }))
+    .into();
+    assert_eq!(err.to_string(), r#"user 7 not found: "gone""#);
+    assert_eq!(
+        UserNotFound::meta().responses[0].description,
+        "User not found"
+    );
+
+    #[derive(OneResponse)]
+    #[oai(status = 429, error_message = "retry in {retry_after}s, {{limit}}")]
+    struct TooManyRequests {
+        retry_after: u32,
+    }
+
+    let err: Error = TooManyRequests { retry_after: 30 }.into();
+    assert_eq!(err.to_string(), "retry in 30s, {limit}");
+
+    /// Bad request
+    #[derive(OneResponse)]
+    #[oai(status = 400)]
+    enum BadRequest {
+        #[oai(error_message = "invalid field `{0}`")]
+        Field(#[oai(header = "X-Field")] String),
+        Other,
+    }
+
+    let err: Error = BadRequest::Field("name".to_string()).into();
+    assert_eq!(err.to_string(), "invalid field `name`");
+    let err: Error = BadRequest::Other.into();
+    assert_eq!(err.to_string(), "Bad request");
+
+    fn describe(resp: &Teapot) -> String {
+        format!("teapot {}", resp.0 .0)
+    }
+
+    #[derive(OneResponse)]
+    #[oai(status = 418, error_message_with = "describe")]
+    struct Teapot(Json<i32>);
+
+    let err: Error = Teapot(Json(1)).into();
+    assert_eq!(err.to_string(), "teapot 1");
+}
+
+#[test]
+fn error_metadata() {
+    /// Bad request
+    #[derive(OneResponse)]
+    #[oai(status = 400)]
+    struct InvalidBody(Json<BadRequestResult>);
+
+    let err: Error = InvalidBody(Json(BadRequestResult {
+        error_code: 1,
+        message: "secret".to_string(),
+    }))
+    .into();
+    assert_eq!(err.to_string(), "Bad request");
+    assert_eq!(
+        err.data::<ErrorMetadata>(),
+        Some(&ErrorMetadata {
+            type_name: std::any::type_name::<InvalidBody>(),
+            variant: None,
+            status: StatusCode::BAD_REQUEST,
+            content_type: Some("application/json; charset=utf-8".to_string()),
+            body: Some(std::any::type_name::<Json<BadRequestResult>>().to_string()),
+        })
+    );
+
+    #[derive(OneResponse)]
+    #[oai(status = 409)]
+    enum Conflict {
+        Name(PlainText<String>),
+        Version,
+    }
+
+    let err: Error = Conflict::Version.into();
+    let metadata = err.data::<ErrorMetadata>().unwrap();
+    assert_eq!(metadata.variant, Some("Version"));
+    assert_eq!(metadata.status, StatusCode::CONFLICT);
+    assert_eq!(metadata.body, None);
+
+    let err: Error = Conflict::Name(PlainText("a".to_string())).into();
+    assert_eq!(err.data::<ErrorMetadata>().unwrap().variant, Some("Name"));
+
+    fn summary(resp: &Locked) -> String {
+        format!("locked by user {}", resp.0 .0.error_code)
+    }
+
+    #[derive(OneResponse)]
+    #[oai(status = 423, error_summary_with = "summary")]
+    struct Locked(Json<BadRequestResult>);
+
+    let err: Error = Locked(Json(BadRequestResult {
+        error_code: 9,
+        message: "secret".to_string(),
+    }))
+    .into();
+    assert_eq!(
+        err.data::<ErrorMetadata>().unwrap().body.as_deref(),
+        Some("locked by user 9")
+    );
+}
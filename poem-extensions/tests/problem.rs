@@ -0,0 +1,247 @@
+use poem::{http::StatusCode, test::TestClient, IntoResponse};
+use poem_extensions::{response, OneResponse, Problem, ProblemResponse, UniResponse};
+use poem_openapi::{
+    payload::{Json, Payload},
+    registry::{MetaApi, MetaSchema, MetaSchemaRef, Registry},
+    types::Type,
+    ApiResponse, Object, OpenApi, OpenApiService,
+};
+use serde_json::{json, Value};
+
+#[derive(Object)]
+struct OutOfCredit {
+    balance: i32,
+}
+
+/// Not found
+#[derive(ProblemResponse)]
+#[oai(status = 404)]
+struct NotFound(Problem);
+
+/// Payment required
+#[derive(ProblemResponse)]
+#[oai(status = 402)]
+struct PaymentRequired(Problem<OutOfCredit>);
+
+async fn body(resp: poem::Response) -> Value {
+    serde_json::from_str(&resp.into_body().into_string().await.unwrap()).unwrap()
+}
+
+#[tokio::test]
+async fn problem_response() {
+    assert_eq!(NotFound::STATUS, 404);
+
+    let meta = NotFound::meta();
+    assert_eq!(meta.responses[0].description, "Not found");
+    assert_eq!(
+        meta.responses[0].content[0].content_type,
+        "application/problem+json"
+    );
+    assert_eq!(
+        meta.responses[0].content[0].schema,
+        MetaSchemaRef::Reference("Problem".to_string())
+    );
+
+    let mut registry = Registry::new();
+    NotFound::register(&mut registry);
+    let schema = &registry.schemas["Problem"];
+    assert_eq!(
+        schema
+            .properties
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>(),
+        ["type", "title", "status", "detail", "instance"]
+    );
+
+    let resp = NotFound(Problem::new().title("Not Found").detail("no user 1")).into_response();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(resp.content_type(), Some("application/problem+json"));
+    assert_eq!(
+        body(resp).await,
+        json!({
+            "title": "Not Found",
+            "status": 404,
+            "detail": "no user 1",
+        })
+    );
+}
+
+#[tokio::test]
+async fn extensions() {
+    assert_eq!(
+        <Problem<OutOfCredit> as Payload>::schema_ref(),
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            all_of: vec![
+                MetaSchemaRef::Reference("Problem".to_string()),
+                OutOfCredit::schema_ref(),
+            ],
+            ..MetaSchema::ANY
+        }))
+    );
+
+    let mut registry = Registry::new();
+    PaymentRequired::register(&mut registry);
+    assert!(registry.schemas.contains_key("Problem"));
+    assert!(registry.schemas.contains_key("OutOfCredit"));
+
+    let resp = PaymentRequired(
+        Problem::with_extensions(OutOfCredit { balance: 30 })
+            .ty("https://example.com/probs/out-of-credit")
+            .instance("/account/12345"),
+    )
+    .into_response();
+    assert_eq!(resp.status(), StatusCode::PAYMENT_REQUIRED);
+    assert_eq!(
+        body(resp).await,
+        json!({
+            "type": "https://example.com/probs/out-of-credit",
+            "status": 402,
+            "instance": "/account/12345",
+            "balance": 30,
+        })
+    );
+}
+
+#[tokio::test]
+async fn one_response_mode() {
+    #[derive(OneResponse)]
+    #[oai(status = 400, problem)]
+    enum BadRequest {
+        Invalid(Problem),
+        OutOfCredit(Problem<OutOfCredit>),
+    }
+
+    let content = &BadRequest::meta().responses[0].content;
+    assert_eq!(content.len(), 1);
+    assert_eq!(
+        content[0].schema,
+        MetaSchemaRef::Inline(Box::new(MetaSchema {
+            one_of: vec![
+                <Problem as Payload>::schema_ref(),
+                <Problem<OutOfCredit> as Payload>::schema_ref(),
+            ],
+            ..MetaSchema::ANY
+        }))
+    );
+
+    let resp = BadRequest::Invalid(Problem::new().status(StatusCode::OK)).into_response();
+    assert_eq!(body(resp).await, json!({ "status": 400 }));
+
+    let resp = BadRequest::OutOfCredit(Problem::with_extensions(OutOfCredit { balance: 1 }))
+        .into_response();
+    assert_eq!(body(resp).await, json!({ "status": 400, "balance": 1 }));
+}
+
+#[tokio::test]
+async fn uni_response() {
+    response! {
+        type UserResponse = {
+            200: Json<i32>,
+            404: NotFound,
+            409: Problem => "conflict",
+        };
+
+        type ConflictResponse = {
+            409: Problem => "conflict",
+        };
+    }
+
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/user", method = "get")]
+        async fn user(&self) -> UserResponse {
+            match 1 {
+                0 => UniResponse::T200(Json(0)),
+                1 => NotFound(Problem::new()).into(),
+                _ => UniResponse::T409(Problem::new()),
+            }
+        }
+
+        #[oai(path = "/conflict", method = "get")]
+        async fn conflict(&self) -> ConflictResponse {
+            UniResponse::T409(Problem::new().title("Conflict"))
+        }
+    }
+
+    let meta: MetaApi = Api::meta().remove(0);
+    let responses = &meta.paths[0].operations[0].responses.responses;
+    assert_eq!(responses[1].status, Some(404));
+    assert_eq!(responses[2].status, Some(409));
+    assert_eq!(
+        responses[2].content[0].content_type,
+        "application/problem+json"
+    );
+
+    let ep = OpenApiService::new(Api, "test", "1.0");
+    let cli = TestClient::new(ep);
+
+    let resp = cli.get("/user").send().await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+    resp.assert_json(json!({ "status": 404 })).await;
+
+    let resp = cli.get("/conflict").send().await;
+    resp.assert_status(StatusCode::CONFLICT);
+    resp.assert_json(json!({ "title": "Conflict", "status": 409 }))
+        .await;
+}
+
+#[tokio::test]
+async fn range_slot() {
+    /// Client error
+    #[derive(ProblemResponse)]
+    #[oai(status_range = "4XX")]
+    struct ClientError(StatusCode, Problem);
+
+    // the status member is the status code the problem is sent with
+    type Resp = response! {
+        4XX: Problem,
+    };
+
+    let resp = Resp::T4XX(
+        StatusCode::CONFLICT,
+        Problem::new().status(StatusCode::NOT_FOUND),
+    )
+    .unwrap()
+    .into_response();
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    assert_eq!(body(resp).await, json!({ "status": 409 }));
+
+    type Errors = response! {
+        4XX: ClientError,
+    };
+
+    let resp = Errors::T4XX(
+        StatusCode::CONFLICT,
+        ClientError(StatusCode::CONFLICT, Problem::new()),
+    )
+    .unwrap()
+    .into_response();
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+    assert_eq!(body(resp).await, json!({ "status": 409 }));
+
+    assert!(Errors::T4XX(
+        StatusCode::CONFLICT,
+        ClientError(StatusCode::NOT_FOUND, Problem::new()),
+    )
+    .is_err());
+
+    let resp = Errors::Current((
+        StatusCode::CONFLICT,
+        ClientError(StatusCode::NOT_FOUND, Problem::new()),
+    ))
+    .into_response();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(body(resp).await, json!({ "status": 404 }));
+
+    let err: poem::Error = Errors::Current((
+        StatusCode::CONFLICT,
+        ClientError(StatusCode::NOT_FOUND, Problem::new()),
+    ))
+    .into();
+    let resp = err.into_response();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(body(resp).await, json!({ "status": 404 }));
+}
@@ -0,0 +1,12 @@
+use poem_extensions::OneResponse;
+
+#[derive(OneResponse)]
+#[oai(status = 200)]
+struct Login {
+    #[oai(body)]
+    body: (),
+    #[oai(cookie = "session", max_age = -1)]
+    session: String,
+}
+
+fn main() {}
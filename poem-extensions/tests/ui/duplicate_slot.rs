@@ -0,0 +1,25 @@
+use poem_extensions::{response, OneResponse};
+use poem_openapi::ApiResponse;
+
+#[derive(OneResponse)]
+#[oai(status = 401)]
+struct Unauthorized;
+
+#[derive(OneResponse)]
+#[oai(status = 401)]
+struct InvalidToken;
+
+type AuthErrors = response! {
+    401: Unauthorized,
+};
+
+type Resp = response! {
+    401: InvalidToken,
+    ..AuthErrors
+};
+
+fn api_response<T: ApiResponse>() {}
+
+fn main() {
+    api_response::<Resp>();
+}
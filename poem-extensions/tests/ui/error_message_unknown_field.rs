@@ -0,0 +1,18 @@
+use poem_extensions::OneResponse;
+
+#[derive(OneResponse)]
+#[oai(status = 429, error_message = "retry in {retry_after}s")]
+struct TooManyRequests {
+    #[oai(body)]
+    body: (),
+    retry_at: u64,
+}
+
+#[derive(OneResponse)]
+#[oai(status = 400)]
+enum BadRequest {
+    #[oai(error_message = "invalid field `{1}`")]
+    InvalidField(()),
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use poem_extensions::{ErrorResponse, OneResponse};
+use poem_openapi::payload::PlainText;
+
+#[derive(OneResponse, Default)]
+#[oai(status = 404)]
+struct NotFound;
+
+fn deleted() -> PlainText<String> {
+    PlainText("deleted".to_string())
+}
+
+#[derive(ErrorResponse)]
+enum UserError {
+    #[oai(status = 404, response = "NotFound")]
+    NotFound,
+    #[oai(status = 404, response = "PlainText<String>", with = "deleted")]
+    Deleted,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use poem_extensions::OneResponse;
+
+#[derive(OneResponse)]
+#[oai(status = 200, header(name = "X-Retry", ty = "i32", value = "soon"))]
+struct Ok;
+
+#[derive(OneResponse)]
+#[oai(status = 201, header(name = "X-Enabled", ty = "Option<bool>", value = "yes"))]
+struct Created;
+
+fn main() {}
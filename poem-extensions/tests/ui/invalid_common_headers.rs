@@ -0,0 +1,15 @@
+use poem_extensions::response;
+
+response! {
+    type Resp = {
+        headers {
+            "X Request": String,
+            "X-Request-Id": String,
+            "x-request-id": String,
+        },
+        200: (),
+        600: (),
+    };
+}
+
+fn main() {}
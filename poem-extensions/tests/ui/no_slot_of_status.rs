@@ -0,0 +1,10 @@
+use poem_extensions::{response, OneResponse};
+use poem_openapi::payload::PlainText;
+
+#[derive(OneResponse)]
+#[oai(status = 404)]
+struct NotFound;
+
+fn main() {
+    let _: response! { 200: PlainText<String>, 400: PlainText<String> } = NotFound.into();
+}
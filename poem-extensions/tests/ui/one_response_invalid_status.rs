@@ -0,0 +1,7 @@
+use poem_extensions::OneResponse;
+
+#[derive(OneResponse)]
+#[oai(status = 600)]
+struct Unknown;
+
+fn main() {}
@@ -0,0 +1,13 @@
+use poem_extensions::response;
+
+type Resp = response! {
+    200: (),
+    600: (),
+    4222: (),
+    100000: (),
+    NOT_FOUD: (),
+    OK: (),
+    9XX: (),
+};
+
+fn main() {}
@@ -0,0 +1,12 @@
+use poem_extensions::{response, OneResponse};
+use poem_openapi::ApiResponse;
+
+#[derive(OneResponse)]
+#[oai(status = 400)]
+struct BadRequest;
+
+fn api_response<T: ApiResponse>() {}
+
+fn main() {
+    api_response::<response! { 404: BadRequest }>();
+}
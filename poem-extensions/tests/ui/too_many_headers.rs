@@ -0,0 +1,22 @@
+use poem_extensions::response;
+
+response! {
+    type Resp = {
+        headers {
+            "X-A": String,
+            "X-B": String,
+            "X-C": String,
+            "X-D": String,
+            "X-E": String,
+            "X-F": String,
+            "X-G": String,
+            "X-H": String,
+            "X-I": String,
+        },
+        200: (),
+    };
+}
+
+fn main() {
+    let _: Option<Resp> = None;
+}
@@ -0,0 +1,8 @@
+use poem_extensions::response;
+
+type Resp = response! {
+    headers { "X-Request-Id": String },
+    200: () => "the greeting",
+};
+
+fn main() {}
@@ -0,0 +1,15 @@
+use poem_extensions::{response, KeepStatus, OneResponse};
+use poem_openapi::payload::PlainText;
+
+#[derive(OneResponse)]
+#[oai(status = 400)]
+struct BadRequest;
+
+#[derive(OneResponse)]
+#[oai(status = 400)]
+struct InvalidParam;
+
+fn main() {
+    let _: response! { 200: PlainText<String>, 400: BadRequest } = InvalidParam.into();
+    let _: response! { 400: KeepStatus<BadRequest> } = BadRequest.into();
+}
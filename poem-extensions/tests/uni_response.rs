@@ -1,9 +1,14 @@
-use poem::{http::StatusCode, test::TestClient};
-use poem_extensions::{response, OneResponse, UniResponse};
+use futures_util::stream::{self, BoxStream, StreamExt};
+use poem::{http::StatusCode, test::TestClient, IntoResponse};
+use poem_extensions::{
+    response, Either, ErrorMetadata, KeepStatus, OneResponse, OtherStatus, SlotStatusError, Status,
+    UniResponse, Widen, WidenResult, WithHeaders,
+};
 use poem_openapi::{
-    payload::Json,
-    registry::{MetaApi, MetaResponse, MetaResponses},
-    ApiResponse, OpenApi, OpenApiService,
+    param::Query,
+    payload::{Binary, EventStream, Json, PlainText},
+    registry::{MetaApi, MetaResponse, MetaResponses, MetaSchemaRef, Registry},
+    ApiResponse, Object, OpenApi, OpenApiService,
 };
 
 #[test]
@@ -135,3 +140,798 @@ async fn multiple_responses() {
     assert_eq!(responses[1].status, Some(201));
     assert_eq!(responses[2].status, Some(502));
 }
+
+#[tokio::test]
+async fn slot_status() {
+    #[derive(ApiResponse)]
+    enum Created {
+        #[oai(status = 201)]
+        Created,
+    }
+
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/plain", method = "get")]
+        async fn plain(
+            &self,
+        ) -> response! {
+               200: PlainText<String>,
+               404: PlainText<String>,
+           } {
+            UniResponse::T404(PlainText("not found".to_string()))
+        }
+
+        #[oai(path = "/keep", method = "get")]
+        async fn keep(
+            &self,
+        ) -> response! {
+               201: KeepStatus<Created>,
+           } {
+            UniResponse::T201(KeepStatus(Created::Created))
+        }
+    }
+
+    let ep = OpenApiService::new(Api, "test", "1.0");
+    let cli = TestClient::new(ep);
+
+    let resp = cli.get("/plain").send().await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+    resp.assert_text("not found").await;
+
+    let resp = cli.get("/keep").send().await;
+    resp.assert_status(StatusCode::CREATED);
+
+    let metas: Vec<MetaApi> = Api::meta();
+    let responses = &metas[0].paths[0].operations[0].responses.responses;
+    assert_eq!(responses[0].status, Some(200));
+    assert_eq!(responses[1].status, Some(404));
+    let responses = &metas[0].paths[1].operations[0].responses.responses;
+    assert_eq!(responses[0].status, Some(201));
+
+    let resp = <response! {
+        200: PlainText<String>,
+        400: PlainText<String>,
+    }>::T400(PlainText("bad request".to_string()))
+    .into_response();
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+    let resp = <response! {
+        200: KeepStatus<StatusCode>,
+    }>::T200(KeepStatus(StatusCode::ACCEPTED))
+    .into_response();
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+}
+
+#[tokio::test]
+async fn event_stream() {
+    type Events = response! {
+        202: EventStream<BoxStream<'static, i32>>,
+    };
+
+    let resp = Events::T202(EventStream::new(stream::iter([1, 2]).boxed())).into_response();
+    assert_eq!(resp.status(), StatusCode::ACCEPTED);
+    assert_eq!(resp.content_type(), Some("text/event-stream"));
+    let body = resp.into_body().into_string().await.unwrap();
+    assert_eq!(body, "data: 1\n\ndata: 2\n\n");
+}
+
+#[tokio::test]
+async fn status_range_and_default() {
+    #[derive(OneResponse)]
+    #[oai(status = 200)]
+    struct Ok;
+
+    /// Client error
+    #[derive(OneResponse)]
+    #[oai(status_range = "4XX")]
+    struct ClientError(StatusCode, PlainText<String>);
+
+    type Resp = response! {
+        200: Ok,
+        4XX: ClientError,
+        default: PlainText<String>,
+    };
+
+    let responses = Resp::meta().responses;
+    assert_eq!(responses.len(), 3);
+    assert_eq!(responses[0].status, Some(200));
+    assert_eq!(responses[1].description, "Client error");
+    assert_eq!(responses[1].status, None);
+    assert_eq!(responses[1].status_range.as_deref(), Some("4XX"));
+    assert_eq!(responses[2].status, None);
+    assert_eq!(responses[2].status_range, None);
+
+    let resp = Resp::T4XX(
+        StatusCode::CONFLICT,
+        ClientError(StatusCode::CONFLICT, PlainText("conflict".to_string())),
+    )
+    .unwrap()
+    .into_response();
+    assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+    let mut resp = Resp::Default(StatusCode::IM_A_TEAPOT, PlainText("teapot".to_string()))
+        .unwrap()
+        .into_response();
+    assert_eq!(resp.status(), StatusCode::IM_A_TEAPOT);
+    assert_eq!(resp.take_body().into_string().await.unwrap(), "teapot");
+}
+
+#[test]
+fn status_range_mismatch() {
+    #[derive(OneResponse)]
+    #[oai(status_range = "4XX")]
+    struct ClientError(StatusCode, PlainText<String>);
+
+    type Resp = response! {
+        4XX: PlainText<String>,
+    };
+
+    let err = Resp::T4XX(
+        StatusCode::INTERNAL_SERVER_ERROR,
+        PlainText("error".to_string()),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        err,
+        SlotStatusError::OutOfRange {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            range: "4XX",
+        }
+    );
+    assert_eq!(err.to_string(), "status code 500 is not in the range 4XX");
+
+    type ErrorResp = response! {
+        4XX: ClientError,
+    };
+
+    let err = ErrorResp::T4XX(
+        StatusCode::CONFLICT,
+        ClientError(StatusCode::NOT_FOUND, PlainText("not found".to_string())),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        err,
+        SlotStatusError::Conflict {
+            status: StatusCode::CONFLICT,
+            response_status: StatusCode::NOT_FOUND,
+        }
+    );
+
+    // the status code of the response itself is sent, a status code out of
+    // the range is an internal server error
+    let resp = ErrorResp::Current((
+        StatusCode::CONFLICT,
+        ClientError(StatusCode::NOT_FOUND, PlainText("not found".to_string())),
+    ));
+    assert_eq!(resp.into_response().status(), StatusCode::NOT_FOUND);
+
+    let resp = ErrorResp::Current((
+        StatusCode::CONFLICT,
+        ClientError(StatusCode::OK, PlainText("ok".to_string())),
+    ));
+    assert_eq!(
+        resp.into_response().status(),
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+}
+
+#[tokio::test]
+async fn other_status() {
+    #[derive(OneResponse)]
+    #[oai(status = 200)]
+    struct Ok;
+
+    /// Early hints
+    #[derive(OneResponse)]
+    #[oai(status = 103)]
+    struct EarlyHints;
+
+    /// Client closed request
+    #[derive(OneResponse)]
+    #[oai(status = 499)]
+    struct ClientClosedRequest(PlainText<String>);
+
+    type Resp = response! {
+        200: Ok,
+        499: ClientClosedRequest,
+        103: EarlyHints,
+        425: PlainText<String>,
+    };
+
+    let responses = Resp::meta().responses;
+    assert_eq!(responses.len(), 4);
+    assert_eq!(responses[0].status, Some(103));
+    assert_eq!(responses[0].description, "Early hints");
+    assert_eq!(responses[1].status, Some(200));
+    assert_eq!(responses[2].status, Some(425));
+    assert_eq!(responses[3].status, Some(499));
+    assert_eq!(responses[3].description, "Client closed request");
+
+    let resp = Resp::with_status::<103, _>(EarlyHints).into_response();
+    assert_eq!(resp.status(), StatusCode::EARLY_HINTS);
+
+    let resp = Resp::with_status::<425, _>(PlainText("too early".to_string())).into_response();
+    assert_eq!(resp.status(), StatusCode::from_u16(425).unwrap());
+
+    let mut resp =
+        Resp::with_status::<499, _>(ClientClosedRequest(PlainText("closed".to_string())))
+            .into_response();
+    assert_eq!(resp.status().as_u16(), 499);
+    assert_eq!(resp.take_body().into_string().await.unwrap(), "closed");
+
+    let resp: OtherStatus<103, EarlyHints, OtherStatus<425, PlainText<String>>> =
+        OtherStatus::Next(OtherStatus::Current(PlainText("too early".to_string())));
+    assert_eq!(
+        resp.into_response().status(),
+        StatusCode::from_u16(425).unwrap()
+    );
+}
+
+#[test]
+fn get_and_take() {
+    #[derive(Debug, PartialEq, OneResponse)]
+    #[oai(status = 404)]
+    struct NotFound;
+
+    type Resp = response! {
+        200: PlainText<String>,
+        404: NotFound,
+    };
+
+    let resp = Resp::T200(PlainText("hello".to_string()));
+    assert_eq!(resp.get::<Status<200>, _>().unwrap().0, "hello");
+    assert!(resp.get::<Status<404>, _>().is_none());
+
+    let resp = Resp::T404(NotFound);
+    let resp = match resp.take::<Status<200>, _>() {
+        Ok(_) => unreachable!(),
+        Err(resp) => resp,
+    };
+    assert_eq!(resp.take::<Status<404>, _>().ok(), Some(NotFound));
+}
+
+#[test]
+fn register() {
+    #[derive(Object)]
+    struct A {
+        value: i32,
+    }
+
+    #[derive(Object)]
+    struct B {
+        value: String,
+    }
+
+    /// Bad request
+    #[derive(OneResponse)]
+    #[oai(status = 400)]
+    struct BadRequest(Json<B>);
+
+    let mut registry = Registry::new();
+    <response! {
+        200: Json<A>,
+        400: BadRequest,
+    }>::register(&mut registry);
+    let mut type_names: Vec<&String> = registry.schemas.keys().collect();
+    type_names.sort();
+    assert_eq!(&type_names, &["A", "B"]);
+}
+
+#[tokio::test]
+async fn multiple_content_types() {
+    #[derive(Object)]
+    struct A {
+        value: i32,
+    }
+
+    type Resp = response! {
+        200: Json<A> | PlainText<String> | Binary<Vec<u8>>,
+        404: PlainText<String>,
+    };
+
+    let responses = Resp::meta().responses;
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0].status, Some(200));
+    assert_eq!(
+        responses[0]
+            .content
+            .iter()
+            .map(|media_type| media_type.content_type)
+            .collect::<Vec<_>>(),
+        [
+            "application/json; charset=utf-8",
+            "text/plain; charset=utf-8",
+            "application/octet-stream"
+        ]
+    );
+
+    let resp = Resp::T200(Either::Left(Json(A { value: 1 }))).into_response();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.content_type(), Some("application/json; charset=utf-8"));
+
+    let mut resp =
+        Resp::T200(Either::Right(Either::Left(PlainText("1".to_string())))).into_response();
+    assert_eq!(resp.content_type(), Some("text/plain; charset=utf-8"));
+    assert_eq!(resp.take_body().into_string().await.unwrap(), "1");
+
+    let resp = Resp::T200(Either::Right(Either::Right(Binary(vec![1])))).into_response();
+    assert_eq!(resp.content_type(), Some("application/octet-stream"));
+}
+
+#[tokio::test]
+async fn as_error() {
+    /// Bad request
+    #[derive(OneResponse)]
+    #[oai(status = 400)]
+    struct BadRequest(PlainText<String>);
+
+    type Errors = response! {
+        400: BadRequest,
+        404: PlainText<String>,
+    };
+
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/number", method = "post")]
+        async fn number(
+            &self,
+            num: Json<u16>,
+        ) -> Result<response! { 200: PlainText<String> }, Errors> {
+            match num.0 {
+                0 => Err(UniResponse::T400(BadRequest(PlainText("zero".to_string())))),
+                1..=100 => Ok(UniResponse::T200(PlainText(num.0.to_string()))),
+                _ => Err(UniResponse::T404(PlainText("too large".to_string()))),
+            }
+        }
+    }
+
+    let ep = OpenApiService::new(Api, "test", "1.0");
+    let cli = TestClient::new(ep);
+
+    let resp = cli.post("/number").body_json(&1).send().await;
+    resp.assert_status_is_ok();
+    resp.assert_text("1").await;
+
+    let resp = cli.post("/number").body_json(&0).send().await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+    resp.assert_text("zero").await;
+
+    let resp = cli.post("/number").body_json(&101).send().await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+    resp.assert_text("too large").await;
+
+    let meta: MetaApi = Api::meta().remove(0);
+    let responses = &meta.paths[0].operations[0].responses.responses;
+    assert_eq!(
+        responses.iter().map(|resp| resp.status).collect::<Vec<_>>(),
+        [Some(200), Some(400), Some(404)]
+    );
+
+    let err: poem::Error = Errors::T400(BadRequest(PlainText("zero".to_string()))).into();
+    assert_eq!(err.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(err.to_string(), "Bad request");
+
+    let err: poem::Error = Errors::T404(PlainText("too large".to_string())).into();
+    assert_eq!(err.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        err.into_response().take_body().into_string().await.unwrap(),
+        "too large"
+    );
+}
+
+#[tokio::test]
+async fn bad_request_handler() {
+    #[derive(Object)]
+    struct ErrorBody {
+        message: String,
+    }
+
+    #[derive(OneResponse)]
+    #[oai(status = 400, bad_request_handler = "bad_request_handler")]
+    struct BadRequest(Json<ErrorBody>);
+
+    fn bad_request_handler(err: poem::Error) -> BadRequest {
+        BadRequest(Json(ErrorBody {
+            message: err.to_string(),
+        }))
+    }
+
+    type Resp = response! {
+        200: PlainText<String>,
+        400: BadRequest,
+        404: PlainText<String>,
+    };
+
+    const { assert!(Resp::BAD_REQUEST_HANDLER) };
+    const { assert!(!<response! { 200: PlainText<String> }>::BAD_REQUEST_HANDLER) };
+
+    #[derive(ApiResponse)]
+    #[oai(bad_request_handler = "handle")]
+    enum Handled {
+        #[oai(status = 400)]
+        BadRequest(PlainText<String>),
+    }
+
+    fn handle(err: poem::Error) -> Handled {
+        Handled::BadRequest(PlainText(err.to_string()))
+    }
+
+    // a parse error is only routed to a slot that can send `400 Bad Request`
+    const { assert!(<response! { 4XX: KeepStatus<Handled> }>::BAD_REQUEST_HANDLER) };
+    const { assert!(<response! { default: KeepStatus<Handled> }>::BAD_REQUEST_HANDLER) };
+    const { assert!(!<response! { 2XX: KeepStatus<Handled> }>::BAD_REQUEST_HANDLER) };
+
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/number", method = "get")]
+        async fn number(&self, num: Query<u16>) -> Resp {
+            UniResponse::T200(PlainText(num.0.to_string()))
+        }
+    }
+
+    let ep = OpenApiService::new(Api, "test", "1.0");
+    let cli = TestClient::new(ep);
+
+    let resp = cli.get("/number").query("num", &1).send().await;
+    resp.assert_status_is_ok();
+    resp.assert_text("1").await;
+
+    let resp = cli.get("/number").query("num", &"a").send().await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+    resp.assert_content_type("application/json; charset=utf-8");
+    resp.assert_json(serde_json::json!({
+        "message": r#"failed to parse parameter `num`: failed to parse "integer(uint16)": invalid digit found in string"#
+    }))
+    .await;
+}
+
+#[tokio::test]
+async fn widen() {
+    /// Bad request
+    #[derive(OneResponse, Debug)]
+    #[oai(status = 400)]
+    struct BadRequest(PlainText<String>);
+
+    /// Not found
+    #[derive(OneResponse, Debug)]
+    #[oai(status = 404)]
+    struct NotFound;
+
+    type Errors = response! {
+        400: BadRequest,
+        404: NotFound,
+    };
+
+    type ApiErrors = response! {
+        400: BadRequest,
+        404: NotFound,
+        500: PlainText<String>,
+    };
+
+    fn check(num: u16) -> Result<u16, BadRequest> {
+        match num {
+            0 => Err(BadRequest(PlainText("zero".to_string()))),
+            _ => Ok(num),
+        }
+    }
+
+    fn find(num: u16) -> Result<u16, Errors> {
+        match check(num)? {
+            1..=100 => Ok(num),
+            _ => Err(NotFound.into()),
+        }
+    }
+
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/number", method = "post")]
+        async fn number(
+            &self,
+            num: Json<u16>,
+        ) -> Result<response! { 200: PlainText<String> }, ApiErrors> {
+            let num = find(num.0).widen()?;
+            Ok(UniResponse::T200(PlainText(num.to_string())))
+        }
+    }
+
+    let ep = OpenApiService::new(Api, "test", "1.0");
+    let cli = TestClient::new(ep);
+
+    let resp = cli.post("/number").body_json(&1).send().await;
+    resp.assert_status_is_ok();
+    resp.assert_text("1").await;
+
+    let resp = cli.post("/number").body_json(&0).send().await;
+    resp.assert_status(StatusCode::BAD_REQUEST);
+    resp.assert_text("zero").await;
+
+    let resp = cli.post("/number").body_json(&101).send().await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+
+    let resp: response! {
+        200: PlainText<String>,
+        404: NotFound,
+    } = NotFound.into();
+    assert_eq!(resp.into_response().status(), StatusCode::NOT_FOUND);
+
+    let resp: ApiErrors = find(0).unwrap_err().widen();
+    assert_eq!(resp.into_response().status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn response_set() {
+    /// Unauthorized
+    #[derive(OneResponse)]
+    #[oai(status = 401)]
+    struct Unauthorized;
+
+    /// Forbidden
+    #[derive(OneResponse)]
+    #[oai(status = 403)]
+    struct Forbidden;
+
+    type AuthErrors = response! {
+        401: Unauthorized,
+        403: Forbidden,
+    };
+
+    type CommonErrors = response! {
+        500: PlainText<String>,
+    };
+
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/number", method = "post")]
+        async fn number(
+            &self,
+            num: Json<u16>,
+        ) -> response! {
+               200: PlainText<String>,
+               ..AuthErrors,
+               ..CommonErrors,
+           } {
+            match num.0 {
+                0 => Unauthorized.into(),
+                1 => Forbidden.into(),
+                2 => UniResponse::T500(PlainText("internal".to_string())),
+                _ => UniResponse::T200(PlainText(num.0.to_string())),
+            }
+        }
+    }
+
+    let ep = OpenApiService::new(Api, "test", "1.0");
+    let cli = TestClient::new(ep);
+
+    let resp = cli.post("/number").body_json(&0).send().await;
+    resp.assert_status(StatusCode::UNAUTHORIZED);
+
+    let resp = cli.post("/number").body_json(&1).send().await;
+    resp.assert_status(StatusCode::FORBIDDEN);
+
+    let resp = cli.post("/number").body_json(&2).send().await;
+    resp.assert_status(StatusCode::INTERNAL_SERVER_ERROR);
+    resp.assert_text("internal").await;
+
+    let resp = cli.post("/number").body_json(&3).send().await;
+    resp.assert_status_is_ok();
+    resp.assert_text("3").await;
+
+    let meta: MetaApi = Api::meta().remove(0);
+    let responses = &meta.paths[0].operations[0].responses.responses;
+    assert_eq!(
+        responses.iter().map(|resp| resp.status).collect::<Vec<_>>(),
+        [Some(200), Some(401), Some(403), Some(500)]
+    );
+    assert_eq!(responses[1].description, "Unauthorized");
+}
+
+#[test]
+fn named_status() {
+    #[derive(OneResponse)]
+    #[oai(status = NOT_FOUND)]
+    struct NotFound;
+
+    type Resp = response! {
+        OK: PlainText<String>,
+        NOT_FOUND: NotFound,
+        TOO_MANY_REQUESTS: (),
+    };
+
+    let responses = Resp::meta().responses;
+    assert_eq!(
+        responses.iter().map(|resp| resp.status).collect::<Vec<_>>(),
+        [Some(200), Some(404), Some(429)]
+    );
+
+    let resp = Resp::T404(NotFound).into_response();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn description() {
+    #[derive(Object)]
+    struct ErrorBody {
+        message: String,
+    }
+
+    /// Bad request
+    #[derive(OneResponse)]
+    #[oai(status = 400)]
+    struct BadRequest(Json<ErrorBody>);
+
+    response! {
+        type Resp = {
+            200: PlainText<String> => "the number",
+            400: BadRequest => "the number is zero",
+            404: Json<ErrorBody> => "the number is too large", example = r#"{"message": "too large"}"#,
+            500: PlainText<String> => "ünïcödé description", example = r#""internal""#,
+        };
+    }
+
+    let responses = Resp::meta().responses;
+    assert_eq!(responses.len(), 4);
+    assert_eq!(responses[0].description, "the number");
+    assert_eq!(responses[1].description, "the number is zero");
+    assert_eq!(responses[2].description, "the number is too large");
+    assert_eq!(responses[3].description, "ünïcödé description");
+
+    let schema = match &responses[2].content[0].schema {
+        MetaSchemaRef::Inline(schema) => schema,
+        MetaSchemaRef::Reference(_) => panic!("the example needs an inline schema"),
+    };
+    assert_eq!(
+        schema.all_of,
+        [MetaSchemaRef::Reference("ErrorBody".to_string())]
+    );
+    assert_eq!(
+        schema.example,
+        Some(serde_json::json!({ "message": "too large" }))
+    );
+
+    // the example is only documented for JSON
+    let schema = match &responses[3].content[0].schema {
+        MetaSchemaRef::Inline(schema) => schema,
+        MetaSchemaRef::Reference(_) => panic!("a string has an inline schema"),
+    };
+    assert_eq!(schema.ty, "string");
+    assert_eq!(schema.example, None);
+
+    // the described slots are constructed as usual
+    let mut resp = Resp::T404(Json(ErrorBody {
+        message: "too large".to_string(),
+    }))
+    .into_response();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(
+        resp.take_body().into_string().await.unwrap(),
+        r#"{"message":"too large"}"#
+    );
+
+    let resp: Resp = BadRequest(Json(ErrorBody {
+        message: "zero".to_string(),
+    }))
+    .into();
+    assert_eq!(resp.into_response().status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn common_headers() {
+    /// Not found
+    #[derive(OneResponse)]
+    #[oai(status = 404)]
+    struct NotFound;
+
+    response! {
+        type Resp = {
+            headers {
+                "X-Request-Id": String => "the id of the request",
+                "X-RateLimit-Remaining": Option<u32>,
+            },
+            200: PlainText<String>,
+            404: NotFound,
+        };
+
+        type Errors = {
+            headers { "X-Request-Id": String },
+            404: NotFound,
+        };
+    }
+
+    struct Api;
+
+    #[OpenApi]
+    impl Api {
+        #[oai(path = "/number", method = "post")]
+        async fn number(&self, num: Json<u16>) -> Resp {
+            let resp = match num.0 {
+                0 => UniResponse::T404(NotFound),
+                _ => UniResponse::T200(PlainText(num.0.to_string())),
+            };
+            WithHeaders::new(resp, ("abc".to_string(), Some(10)))
+        }
+    }
+
+    let ep = OpenApiService::new(Api, "test", "1.0");
+    let cli = TestClient::new(ep);
+
+    let resp = cli.post("/number").body_json(&1).send().await;
+    resp.assert_status_is_ok();
+    resp.assert_header("x-request-id", "abc");
+    resp.assert_header("x-ratelimit-remaining", "10");
+    resp.assert_text("1").await;
+
+    let resp = cli.post("/number").body_json(&0).send().await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+    resp.assert_header("x-request-id", "abc");
+
+    let responses = Resp::meta().responses;
+    assert_eq!(responses.len(), 2);
+    for response in &responses {
+        let headers = &response.headers;
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers[0].name, "X-Request-Id");
+        assert_eq!(
+            headers[0].description.as_deref(),
+            Some("the id of the request")
+        );
+        assert!(headers[0].required);
+        assert_eq!(headers[1].name, "X-RateLimit-Remaining");
+        assert!(!headers[1].required);
+    }
+
+    let resp: Errors = WithHeaders::new(UniResponse::T404(NotFound), ("abc".to_string(),));
+    let err: poem::Error = resp.into();
+    assert_eq!(err.to_string(), "Not found");
+    let resp = err.into_response();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(resp.headers()["x-request-id"], "abc");
+}
+
+#[test]
+fn error_metadata() {
+    /// Not found
+    #[derive(OneResponse)]
+    #[oai(status = 404)]
+    struct NotFound(PlainText<String>);
+
+    response! {
+        type Errors = {
+            headers { "X-Request-Id": String },
+            404: NotFound,
+        };
+    }
+
+    let resp: response! { 404: NotFound } =
+        UniResponse::T404(NotFound(PlainText("secret".to_string())));
+    let err: poem::Error = resp.into();
+    let metadata = err.data::<ErrorMetadata>().unwrap();
+    assert_eq!(metadata.type_name, std::any::type_name::<NotFound>());
+    assert_eq!(metadata.status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        metadata.content_type.as_deref(),
+        Some("text/plain; charset=utf-8")
+    );
+
+    let errors: Errors = WithHeaders::new(
+        UniResponse::T404(NotFound(PlainText("secret".to_string()))),
+        ("r-1".to_string(),),
+    );
+    let err: poem::Error = errors.into();
+    assert_eq!(err.to_string(), "Not found");
+    assert_eq!(
+        err.data::<ErrorMetadata>().unwrap().type_name,
+        std::any::type_name::<NotFound>()
+    );
+}